@@ -3,4 +3,19 @@ pub const U64_L: usize = 8;
 pub const U16_L: usize = 2;
 pub const BOOL_L: usize = 1;
 pub const OPTION_L: usize = 1;
-pub const U8_L: usize = 1;
\ No newline at end of file
+pub const U8_L: usize = 1;
+pub const I64_L: usize = 8;
+pub const U128_L: usize = 16;
+pub const VEC_L: usize = 4;
+
+pub const MIN_ASSETS: usize = 3;
+pub const MAX_ASSETS: usize = 8;
+pub const MIN_WEIGHT: u16 = 100;  // Minimum asset weight in basis points
+
+pub const MINIMUM_LIQUIDITY: u64 = 1000;  // LP permanently locked by the first deposit
+
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;  // Scale of reward_per_share
+pub const MAX_REWARD_STREAMS: usize = 4;
+
+pub const MAX_QUEUED_ACTIONS: usize = 8;
+pub const MAX_ACTION_L: usize = 256;  // Max Borsh encoded size of an AdminAction, LP metadata being the largest
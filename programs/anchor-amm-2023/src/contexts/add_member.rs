@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use crate::state::config::Config;
+use crate::state::admin_action::{AdminAction, AdminQueue};
+use crate::state::member::Member;
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddMember<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"member", config.key().as_ref(), wallet.as_ref()],
+        bump,
+        space = Member::LEN
+    )]
+    pub member: Account<'info, Member>,
+    /// Queue holding the action, needed once the pool has a timelock
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"admin_queue", config.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Option<Box<Account<'info, AdminQueue>>>,
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> AddMember<'info> {
+    pub fn add_member(
+        &mut self,
+        bumps: &BTreeMap<String, u8>,
+        wallet: Pubkey
+    ) -> Result<()> {
+        AdminAction::AddMember { wallet }.authorize(&self.config, self.user.key(), self.admin_queue.as_deref_mut())?;
+        self.member.init(
+            self.config.key(),
+            wallet,
+            *bumps.get("member").ok_or(AmmError::BumpError)?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::state::config::Config;
+use crate::state::admin_action::{AdminAction, AdminQueue};
+use crate::state::farm::Farm;
+
+#[derive(Accounts)]
+pub struct AddRewardStream<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm
+    )]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    /// Queue holding the action, needed once the pool has a timelock
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"admin_queue", config.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Option<Box<Account<'info, AdminQueue>>>,
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> AddRewardStream<'info> {
+    pub fn add_reward_stream(
+        &mut self,
+        emission_rate: u64,
+        start_ts: i64,
+        end_ts: i64
+    ) -> Result<()> {
+        let reward_mint = self.reward_mint.key();
+        AdminAction::AddRewardStream { reward_mint, emission_rate, start_ts, end_ts }.authorize(&self.config, self.user.key(), self.admin_queue.as_deref_mut())?;
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        self.farm.add_stream(reward_mint, emission_rate, start_ts, end_ts)
+    }
+}
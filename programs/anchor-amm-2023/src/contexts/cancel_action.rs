@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::has_role;
+use crate::state::config::Config;
+use crate::state::role::Role;
+use crate::state::admin_action::AdminQueue;
+
+#[derive(Accounts)]
+pub struct CancelAction<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"admin_queue", config.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Box<Account<'info, AdminQueue>>,
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>
+}
+
+impl<'info> CancelAction<'info> {
+    // Guardians can veto pending changes as well as pause
+    pub fn cancel_action(
+        &mut self,
+        id: u64
+    ) -> Result<()> {
+        has_role!(self, Role::Guardian);
+        self.admin_queue.remove(id)?;
+        Ok(())
+    }
+}
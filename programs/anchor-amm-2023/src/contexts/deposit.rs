@@ -1,10 +1,17 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, MintTo, transfer, mint_to};
 use anchor_spl::associated_token::AssociatedToken;
-use constant_product_curve::ConstantProduct;
-use crate::{assert_non_zero, assert_not_locked, assert_not_expired};
+use constant_product_curve::{ConstantProduct, LiquidityPair};
+use crate::{assert_non_zero, assert_not_locked, assert_not_expired, assert_no_flash_loan};
+use crate::helpers::math::{zap_swap_amount, initial_liquidity};
+use crate::constants::MINIMUM_LIQUIDITY;
 use crate::state::config::Config;
+use crate::state::observation::Observations;
+use crate::state::member::Member;
 use crate::errors::AmmError;
+use crate::helpers::access::{check_access, check_owner_not_blocked};
+use crate::helpers::token::{init_user_token_accounts, wrap_sol, unwrap_sol};
+use crate::helpers::pool::update_twap;
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
@@ -30,30 +37,33 @@ pub struct Deposit<'info> {
         associated_token::authority = auth,
     )]
     pub vault_y: Box<Account<'info, TokenAccount>>,
+    /// Pool owned LP account the first deposit locks MINIMUM_LIQUIDITY in
     #[account(
         mut,
-        associated_token::mint = config.mint_x,
-        associated_token::authority = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = auth,
     )]
-    pub user_x: Box<Account<'info, TokenAccount>>,
+    pub vault_lp: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Any X token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_x: UncheckedAccount<'info>,
+    /// CHECK: Any Y token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_y: UncheckedAccount<'info>,
+    /// CHECK: Any LP token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_lp: UncheckedAccount<'info>,
     #[account(
         mut,
-        associated_token::mint = config.mint_y,
-        associated_token::authority = user,
-    )]
-    pub user_y: Box<Account<'info, TokenAccount>>,
-    #[account(
-        init_if_needed,
-        payer = user,
-        associated_token::mint = mint_lp,
-        associated_token::authority = user,
+        token::mint = mint_lp
     )]
-    pub user_lp: Box<Account<'info, TokenAccount>>,
+    pub recipient_lp: Option<Box<Account<'info, TokenAccount>>>,
     
     /// CHECK: just a pda for signing
     #[account(seeds = [b"auth"], bump = config.auth_bump)]
     pub auth: UncheckedAccount<'info>,
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
         seeds = [
@@ -63,6 +73,28 @@ pub struct Deposit<'info> {
         bump = config.config_bump,
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.bump
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+    /// CHECK: Blocklist entry of the user, which must not exist
+    #[account(
+        seeds = [b"blocked", config.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub blocked: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of user_x, checked in the handler
+    pub blocked_x: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of user_y, checked in the handler
+    pub blocked_y: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of recipient_lp, or of user_lp without one
+    pub blocked_lp: UncheckedAccount<'info>,
+    /// Allowlist entry of the user, only checked in gated pools
+    pub member: Option<Box<Account<'info, Member>>>,
+    /// Gate token account of the user, only checked in gated pools
+    pub gate_token: Option<Box<Account<'info, TokenAccount>>>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -70,17 +102,24 @@ pub struct Deposit<'info> {
 
 impl<'info> Deposit<'info> {
     pub fn deposit(
-        &self,
-        amount: u64, // Amount of LP token to claim
+        &mut self,
+        amount: u64, // Amount of LP token to claim, or the minimum to claim on the first deposit
         max_x: u64, // Max amount of X we are willing to deposit
         max_y: u64, // Max amount of Y we are willing to deposit
         expiration: i64,
+        wrap_sol: bool, // Pay a native mint side in lamports, closing wSOL ATAs created here
     ) -> Result<()> {
         assert_not_locked!(self.config.locked);
+        assert_no_flash_loan!(self.config.flash_loan_amount);
         assert_not_expired!(expiration);
         assert_non_zero!([amount, max_x, max_y]);
+        require!(self.blocked.data_is_empty(), AmmError::WalletBlocked);
+        check_access(&self.config, &self.user.key(), self.member.as_deref(), self.gate_token.as_deref(), false)?;
+        let created = self.init_user_accounts()?;
+        self.check_blocklist()?;
 
-        let (x,y) = match self.mint_lp.supply == 0 && self.vault_x.amount == 0 && self.vault_y.amount == 0 {
+        let first = self.mint_lp.supply == 0 && self.vault_x.amount == 0 && self.vault_y.amount == 0;
+        let (x,y) = match first {
             true => (max_x, max_y),
             false => {
                 let amounts = ConstantProduct::xy_deposit_amounts_from_l(
@@ -96,9 +135,236 @@ impl<'info> Deposit<'info> {
 
         // Check for slippage
         require!(x <= max_x && y <= max_y, AmmError::SlippageExceeded);
+        // The first deposit mints the geometric mean, less the part that is locked forever
+        let amount = match first {
+            true => {
+                let liquidity = initial_liquidity(x, y)?;
+                require!(liquidity - MINIMUM_LIQUIDITY >= amount, AmmError::SlippageExceeded);
+                liquidity - MINIMUM_LIQUIDITY
+            },
+            false => amount
+        };
+        self.config.check_deposit_cap(self.vault_x.amount, self.vault_y.amount, x, y)?;
+        if wrap_sol {
+            self.wrap_sol(x, y)?;
+        }
+        self.deposit_tokens(true, x)?;
+        self.deposit_tokens(false, y)?;
+        if wrap_sol {
+            self.unwrap_sol(created)?;
+        }
+        if first {
+            self.mint_lp_to(self.vault_lp.to_account_info(), MINIMUM_LIQUIDITY)?;
+        }
+        self.mint_lp_tokens(amount)?;
+        update_twap(&mut self.config, &mut self.observations, &mut self.vault_x, &mut self.vault_y)
+    }
+
+    pub fn deposit_exact(
+        &mut self,
+        is_x: bool,
+        amount_in: u64, // Amount of X or Y we want to deposit
+        max_other: u64, // Max amount of the other token we are willing to deposit
+        min_lp: u64, // Min amount of LP token we are willing to claim
+        expiration: i64,
+        wrap_sol: bool, // Pay a native mint side in lamports, closing wSOL ATAs created here
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        assert_no_flash_loan!(self.config.flash_loan_amount);
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount_in, max_other, self.mint_lp.supply, self.vault_x.amount, self.vault_y.amount]);
+        require!(self.blocked.data_is_empty(), AmmError::WalletBlocked);
+        check_access(&self.config, &self.user.key(), self.member.as_deref(), self.gate_token.as_deref(), false)?;
+        let created = self.init_user_accounts()?;
+        self.check_blocklist()?;
+
+        let reserve_in = match is_x {
+            true => self.vault_x.amount,
+            false => self.vault_y.amount
+        };
+
+        // LP matching the fixed side, rounded down
+        let mut amount = u64::try_from(
+            amount_in as u128 * self.mint_lp.supply as u128 / reserve_in as u128
+        ).map_err(|_| AmmError::Overflow)?;
+
+        let mut amounts = ConstantProduct::xy_deposit_amounts_from_l(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            amount,
+            6
+        ).map_err(AmmError::from)?;
+
+        // Rounding can ask for slightly more than the fixed amount, so step down one LP unit
+        if (is_x && amounts.x > amount_in) || (!is_x && amounts.y > amount_in) {
+            amount = amount.checked_sub(1).ok_or(AmmError::Underflow)?;
+            amounts = ConstantProduct::xy_deposit_amounts_from_l(
+                self.vault_x.amount,
+                self.vault_y.amount,
+                self.mint_lp.supply,
+                amount,
+                6
+            ).map_err(AmmError::from)?;
+        }
+
+        let (x, y) = (amounts.x, amounts.y);
+        let (exact, other) = match is_x {
+            true => (x, y),
+            false => (y, x)
+        };
+
+        // Check for slippage
+        require!(exact <= amount_in && other <= max_other && amount >= min_lp, AmmError::SlippageExceeded);
+        assert_non_zero!([amount, x, y]);
+        self.config.check_deposit_cap(self.vault_x.amount, self.vault_y.amount, x, y)?;
+        if wrap_sol {
+            self.wrap_sol(x, y)?;
+        }
         self.deposit_tokens(true, x)?;
         self.deposit_tokens(false, y)?;
-        self.mint_lp_tokens(amount)
+        if wrap_sol {
+            self.unwrap_sol(created)?;
+        }
+        self.mint_lp_tokens(amount)?;
+        update_twap(&mut self.config, &mut self.observations, &mut self.vault_x, &mut self.vault_y)
+    }
+
+    pub fn deposit_single(
+        &mut self,
+        is_x: bool,
+        amount_in: u64, // Amount of X or Y to deposit
+        min_lp: u64, // Min amount of LP token we are willing to claim
+        expiration: i64,
+        wrap_sol: bool, // Pay a native mint side in lamports, closing wSOL ATAs created here
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        // The deposit trades against the pool, so it's paused and banded like a swap
+        require!(!self.config.swaps_paused, AmmError::SwapsPaused);
+        assert_no_flash_loan!(self.config.flash_loan_amount);
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount_in, self.mint_lp.supply, self.vault_x.amount, self.vault_y.amount]);
+        require!(self.blocked.data_is_empty(), AmmError::WalletBlocked);
+        check_access(&self.config, &self.user.key(), self.member.as_deref(), self.gate_token.as_deref(), false)?;
+        let created = self.init_user_accounts()?;
+        self.check_blocklist()?;
+        require!(self.config.oracle.is_none(), AmmError::OraclePool);
+
+        let (reserve_in, reserve_out, p) = match is_x {
+            true => (self.vault_x.amount, self.vault_y.amount, LiquidityPair::X),
+            false => (self.vault_y.amount, self.vault_x.amount, LiquidityPair::Y)
+        };
+
+        // Swap just enough of the deposit that the remainder matches the pool ratio
+        let swap_amount = zap_swap_amount(reserve_in, amount_in, self.config.fee)?;
+        let mut curve = ConstantProduct::init(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.vault_x.amount,
+            self.config.fee,
+            None
+        ).map_err(AmmError::from)?;
+        let res = curve.swap(p, swap_amount, 0).map_err(AmmError::from)?;
+        assert_non_zero!([res.deposit, res.withdraw]);
+
+        // The swapped tokens never leave the vaults, they are deposited straight back
+        let (reserve_in, reserve_out) = (reserve_in + res.deposit, reserve_out - res.withdraw);
+        let remainder = amount_in.checked_sub(res.deposit).ok_or(AmmError::Underflow)?;
+        let supply = self.mint_lp.supply as u128;
+        let amount = u64::try_from(std::cmp::min(
+            remainder as u128 * supply / reserve_in as u128,
+            res.withdraw as u128 * supply / reserve_out as u128
+        )).map_err(|_| AmmError::Overflow)?;
+
+        // Check for slippage
+        require!(amount >= min_lp, AmmError::SlippageExceeded);
+        assert_non_zero!([amount]);
+        let (deposit_x, deposit_y) = match is_x {
+            true => (amount_in, 0),
+            false => (0, amount_in)
+        };
+        self.config.check_deposit_cap(self.vault_x.amount, self.vault_y.amount, deposit_x, deposit_y)?;
+        require!(
+            self.config.check_price_band(
+                self.vault_x.amount,
+                self.vault_y.amount,
+                self.vault_x.amount + deposit_x,
+                self.vault_y.amount + deposit_y,
+                Clock::get()?.unix_timestamp
+            ),
+            AmmError::CircuitBreaker
+        );
+        if wrap_sol {
+            self.wrap_sol(deposit_x, deposit_y)?;
+        }
+        self.deposit_tokens(is_x, amount_in)?;
+        if wrap_sol {
+            self.unwrap_sol(created)?;
+        }
+        self.mint_lp_tokens(amount)?;
+        update_twap(&mut self.config, &mut self.observations, &mut self.vault_x, &mut self.vault_y)
+    }
+
+    // Owners of the user's token accounts and of whichever account receives the LP tokens
+    // can't be blocked either
+    pub fn check_blocklist(
+        &self
+    ) -> Result<()> {
+        let pool = self.config.key();
+        let lp = match &self.recipient_lp {
+            Some(recipient) => recipient.to_account_info(),
+            None => self.user_lp.to_account_info()
+        };
+        check_owner_not_blocked(&pool, &self.user_x, &self.blocked_x)?;
+        check_owner_not_blocked(&pool, &self.user_y, &self.blocked_y)?;
+        check_owner_not_blocked(&pool, &lp, &self.blocked_lp)
+    }
+
+    pub fn init_user_accounts(
+        &self
+    ) -> Result<[bool; 2]> {
+        let [x, y, _] = init_user_token_accounts(
+            [
+                (self.user_x.to_account_info(), self.mint_x.to_account_info()),
+                (self.user_y.to_account_info(), self.mint_y.to_account_info()),
+                (self.user_lp.to_account_info(), self.mint_lp.to_account_info())
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info(),
+            self.associated_token_program.to_account_info(),
+            self.system_program.to_account_info()
+        )?;
+        Ok([x, y])
+    }
+
+    pub fn wrap_sol(
+        &self,
+        x: u64,
+        y: u64
+    ) -> Result<()> {
+        wrap_sol(
+            [
+                (self.mint_x.key(), self.user_x.to_account_info(), x),
+                (self.mint_y.key(), self.user_y.to_account_info(), y)
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info(),
+            self.system_program.to_account_info()
+        )
+    }
+
+    pub fn unwrap_sol(
+        &self,
+        created: [bool; 2]
+    ) -> Result<()> {
+        unwrap_sol(
+            [
+                (self.mint_x.key(), self.user_x.to_account_info(), created[0]),
+                (self.mint_y.key(), self.user_y.to_account_info(), created[1])
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info()
+        )
     }
 
     pub fn deposit_tokens(
@@ -123,9 +389,21 @@ impl<'info> Deposit<'info> {
         &self,
         amount:u64
     ) -> Result<()> {        
+        let to = match &self.recipient_lp {
+            Some(recipient) => recipient.to_account_info(),
+            None => self.user_lp.to_account_info()
+        };
+        self.mint_lp_to(to, amount)
+    }
+
+    pub fn mint_lp_to(
+        &self,
+        to: AccountInfo<'info>,
+        amount: u64
+    ) -> Result<()> {
         let accounts = MintTo {
             mint: self.mint_lp.to_account_info(),
-            to: self.user_lp.to_account_info(),
+            to,
             authority: self.auth.to_account_info(),
         };
 
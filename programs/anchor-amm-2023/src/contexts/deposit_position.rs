@@ -0,0 +1,330 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, MintTo, SetAuthority, transfer, mint_to, set_authority};
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use anchor_spl::associated_token::AssociatedToken;
+use constant_product_curve::ConstantProduct;
+use crate::{assert_non_zero, assert_not_locked, assert_not_expired, assert_no_flash_loan};
+use crate::state::config::Config;
+use crate::state::observation::Observations;
+use crate::state::member::Member;
+use crate::state::position::Position;
+use crate::errors::AmmError;
+use crate::constants::MINIMUM_LIQUIDITY;
+use crate::helpers::math::initial_liquidity;
+use crate::helpers::access::{check_access, check_owner_not_blocked};
+use crate::helpers::token::{init_user_token_accounts, wrap_sol, unwrap_sol};
+use crate::helpers::pool::update_twap;
+
+#[derive(Accounts)]
+pub struct DepositPosition<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = auth,
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = auth,
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    /// Pool owned LP account the first deposit locks MINIMUM_LIQUIDITY in
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = auth,
+    )]
+    pub vault_lp: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Any X token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_x: UncheckedAccount<'info>,
+    /// CHECK: Any Y token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_y: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = user,
+        mint::decimals = 0,
+        mint::authority = auth
+    )]
+    pub nft_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = nft_mint,
+        associated_token::authority = user
+    )]
+    pub user_nft: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"position", nft_mint.key().as_ref()],
+        bump,
+        space = Position::LEN
+    )]
+    pub position: Box<Account<'info, Position>>,
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = position
+    )]
+    pub position_lp: Box<Account<'info, TokenAccount>>,
+    /// CHECK: just a pda for signing
+    #[account(seeds = [b"auth"], bump = config.auth_bump)]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.bump
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+    /// CHECK: Blocklist entry of the user, which must not exist
+    #[account(
+        seeds = [b"blocked", config.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub blocked: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of user_x, checked in the handler
+    pub blocked_x: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of user_y, checked in the handler
+    pub blocked_y: UncheckedAccount<'info>,
+    /// Allowlist entry of the user, only checked in gated pools
+    pub member: Option<Box<Account<'info, Member>>>,
+    /// Gate token account of the user, only checked in gated pools
+    pub gate_token: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> DepositPosition<'info> {
+    // Same as deposit, but the LP tokens are held by a position represented by a new 1 of 1 NFT
+    pub fn deposit_position(
+        &mut self,
+        bumps: &BTreeMap<String, u8>,
+        amount: u64, // Amount of LP token to claim, or the minimum to claim on the first deposit
+        max_x: u64, // Max amount of X we are willing to deposit
+        max_y: u64, // Max amount of Y we are willing to deposit
+        expiration: i64,
+        wrap_sol: bool, // Pay a native mint side in lamports, closing wSOL ATAs created here
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        assert_no_flash_loan!(self.config.flash_loan_amount);
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount, max_x, max_y]);
+        require!(self.blocked.data_is_empty(), AmmError::WalletBlocked);
+        check_access(&self.config, &self.user.key(), self.member.as_deref(), self.gate_token.as_deref(), false)?;
+        let created = self.init_user_accounts()?;
+        self.check_blocklist()?;
+        let now = Clock::get()?.unix_timestamp;
+
+        let first = self.mint_lp.supply == 0 && self.vault_x.amount == 0 && self.vault_y.amount == 0;
+        let (x,y) = match first {
+            true => (max_x, max_y),
+            false => {
+                let amounts = ConstantProduct::xy_deposit_amounts_from_l(
+                    self.vault_x.amount,
+                    self.vault_y.amount,
+                    self.mint_lp.supply,
+                    amount,
+                    6
+                ).map_err(AmmError::from)?;
+                (amounts.x, amounts.y)
+            }
+        };
+
+        // Check for slippage
+        require!(x <= max_x && y <= max_y, AmmError::SlippageExceeded);
+        // The first deposit mints the geometric mean, less the part that is locked forever
+        let (amount, locked) = match first {
+            true => {
+                let liquidity = initial_liquidity(x, y)?;
+                require!(liquidity - MINIMUM_LIQUIDITY >= amount, AmmError::SlippageExceeded);
+                (liquidity - MINIMUM_LIQUIDITY, MINIMUM_LIQUIDITY)
+            },
+            false => (amount, 0)
+        };
+        self.config.check_deposit_cap(self.vault_x.amount, self.vault_y.amount, x, y)?;
+
+        let (x1, y1) = (
+            self.vault_x.amount.checked_add(x).ok_or(AmmError::Overflow)?,
+            self.vault_y.amount.checked_add(y).ok_or(AmmError::Overflow)?
+        );
+        self.position.set_inner(Position {
+            config: self.config.key(),
+            nft_mint: self.nft_mint.key(),
+            deposited_x: x,
+            deposited_y: y,
+            lp_amount: amount,
+            entry_ts: now,
+            entry_k: x1 as u128 * y1 as u128,
+            entry_supply: self.mint_lp.supply.checked_add(amount + locked).ok_or(AmmError::Overflow)?,
+            bump: *bumps.get("position").ok_or(AmmError::BumpError)?
+        });
+
+        if wrap_sol {
+            self.wrap_sol(x, y)?;
+        }
+        self.deposit_tokens(true, x)?;
+        self.deposit_tokens(false, y)?;
+        if wrap_sol {
+            self.unwrap_sol(created)?;
+        }
+        if first {
+            self.mint_tokens(self.mint_lp.to_account_info(), self.vault_lp.to_account_info(), locked)?;
+        }
+        self.mint_tokens(self.mint_lp.to_account_info(), self.position_lp.to_account_info(), amount)?;
+        self.mint_nft()?;
+        update_twap(&mut self.config, &mut self.observations, &mut self.vault_x, &mut self.vault_y)
+    }
+
+    // Owners of the user's token accounts can't be blocked either
+    pub fn check_blocklist(
+        &self
+    ) -> Result<()> {
+        let pool = self.config.key();
+        check_owner_not_blocked(&pool, &self.user_x, &self.blocked_x)?;
+        check_owner_not_blocked(&pool, &self.user_y, &self.blocked_y)
+    }
+
+    pub fn init_user_accounts(
+        &self
+    ) -> Result<[bool; 2]> {
+        init_user_token_accounts(
+            [
+                (self.user_x.to_account_info(), self.mint_x.to_account_info()),
+                (self.user_y.to_account_info(), self.mint_y.to_account_info())
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info(),
+            self.associated_token_program.to_account_info(),
+            self.system_program.to_account_info()
+        )
+    }
+
+    pub fn wrap_sol(
+        &self,
+        x: u64,
+        y: u64
+    ) -> Result<()> {
+        wrap_sol(
+            [
+                (self.mint_x.key(), self.user_x.to_account_info(), x),
+                (self.mint_y.key(), self.user_y.to_account_info(), y)
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info(),
+            self.system_program.to_account_info()
+        )
+    }
+
+    pub fn unwrap_sol(
+        &self,
+        created: [bool; 2]
+    ) -> Result<()> {
+        unwrap_sol(
+            [
+                (self.mint_x.key(), self.user_x.to_account_info(), created[0]),
+                (self.mint_y.key(), self.user_y.to_account_info(), created[1])
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info()
+        )
+    }
+
+    pub fn deposit_tokens(
+        &self,
+        is_x: bool,
+        amount: u64
+    ) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.user_x.to_account_info(), self.vault_x.to_account_info()),
+            false => (self.user_y.to_account_info(), self.vault_y.to_account_info())
+        };
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+        };
+        let ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer(ctx, amount)
+    }
+
+    // Mint exactly one NFT and remove the mint authority so there can never be another
+    pub fn mint_nft(
+        &self
+    ) -> Result<()> {
+        self.mint_tokens(self.nft_mint.to_account_info(), self.user_nft.to_account_info(), 1)?;
+
+        let accounts = SetAuthority {
+            current_authority: self.auth.to_account_info(),
+            account_or_mint: self.nft_mint.to_account_info()
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+        set_authority(ctx, AuthorityType::MintTokens, None)
+    }
+
+    pub fn mint_tokens(
+        &self,
+        mint: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        amount: u64
+    ) -> Result<()> {
+        let accounts = MintTo {
+            mint,
+            to,
+            authority: self.auth.to_account_info(),
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+        mint_to(ctx, amount)
+    }
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::has_role;
+use crate::state::config::Config;
+use crate::state::admin_action::{AdminAction, AdminQueue};
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct ExecuteAction<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"admin_queue", config.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Box<Account<'info, AdminQueue>>,
+    #[account(
+        mut,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>
+}
+
+impl<'info> ExecuteAction<'info> {
+    pub fn execute_action(
+        &mut self,
+        id: u64
+    ) -> Result<()> {
+        let queued = self.admin_queue.remove(id)?;
+        require!(Clock::get()?.unix_timestamp >= queued.eta, AmmError::ActionNotReady);
+
+        let action = AdminAction::try_from_slice(&queued.data).map_err(|_| AmmError::InvalidAction)?;
+        has_role!(self, action.role());
+        action.apply(&mut self.config)
+    }
+}
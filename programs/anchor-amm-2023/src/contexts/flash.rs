@@ -0,0 +1,188 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, transfer};
+use anchor_spl::associated_token::AssociatedToken;
+use solana_program::sysvar::instructions::{self, load_current_index_checked, load_instruction_at_checked};
+use crate::{assert_not_locked, assert_non_zero, assert_no_flash_loan};
+use crate::state::config::Config;
+use crate::errors::AmmError;
+use crate::helpers::token::init_user_token_accounts;
+
+// Position of `config` in the Flash accounts, used to match the repay instruction
+const CONFIG_INDEX: usize = 8;
+
+#[derive(Accounts)]
+pub struct Flash<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    /// CHECK: Any X token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_x: UncheckedAccount<'info>,
+    /// CHECK: Any Y token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_y: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = auth
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = auth
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    /// CHECK: just a pda for signing
+    #[account(seeds = [b"auth"], bump = config.auth_bump)]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: Instructions sysvar, used to find the matching flash_repay
+    #[account(address = instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> Flash<'info> {
+    pub fn flash_loan(
+        &mut self,
+        is_x: bool,
+        amount: u64
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        assert_no_flash_loan!(self.config.flash_loan_amount);
+        assert_non_zero!([amount]);
+        self.init_user_accounts()?;
+
+        self.check_repaid()?;
+
+        self.config.flash_loan_amount = amount;
+        self.config.flash_loan_is_x = is_x;
+        self.withdraw_token(is_x, amount)
+    }
+
+    pub fn flash_repay(
+        &mut self
+    ) -> Result<()> {
+        let amount = self.config.flash_loan_amount;
+        require!(amount != 0, AmmError::NoFlashLoan);
+        self.init_user_accounts()?;
+
+        // Fee is rounded up and stays in the vault for LPs
+        let fee = (amount as u128 * self.config.flash_fee as u128).div_ceil(10000);
+        let repay = amount.checked_add(fee as u64).ok_or(AmmError::Overflow)?;
+
+        let is_x = self.config.flash_loan_is_x;
+        self.config.flash_loan_amount = 0;
+        self.config.flash_loan_is_x = false;
+        self.deposit_token(is_x, repay)
+    }
+
+    pub fn init_user_accounts(
+        &self
+    ) -> Result<()> {
+        init_user_token_accounts(
+            [
+                (self.user_x.to_account_info(), self.mint_x.to_account_info()),
+                (self.user_y.to_account_info(), self.mint_y.to_account_info())
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info(),
+            self.associated_token_program.to_account_info(),
+            self.system_program.to_account_info()
+        )?;
+        Ok(())
+    }
+
+    // Loans must be top level instructions followed by a flash_repay for the same pool
+    pub fn check_repaid(
+        &self
+    ) -> Result<()> {
+        let ixs = self.instructions.to_account_info();
+        let current = load_current_index_checked(&ixs)? as usize;
+        let ix = load_instruction_at_checked(current, &ixs)?;
+        require_keys_eq!(ix.program_id, crate::ID, AmmError::FlashLoanCpi);
+
+        let mut index = current + 1;
+        while let Ok(ix) = load_instruction_at_checked(index, &ixs) {
+            if ix.program_id == crate::ID
+                && ix.data.get(..8) == Some(&crate::instruction::FlashRepay::DISCRIMINATOR[..])
+                && ix.accounts.get(CONFIG_INDEX).map(|a| a.pubkey) == Some(self.config.key())
+            {
+                return Ok(());
+            }
+            index += 1;
+        }
+        err!(AmmError::FlashLoanNotRepaid)
+    }
+
+    pub fn deposit_token(
+        &mut self,
+        is_x: bool,
+        amount: u64
+    ) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.user_x.to_account_info(), self.vault_x.to_account_info()),
+            false => (self.user_y.to_account_info(), self.vault_y.to_account_info())
+        };
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            accounts
+        );
+
+        transfer(ctx, amount)
+    }
+
+    pub fn withdraw_token(
+        &mut self,
+        is_x: bool,
+        amount: u64
+    ) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.user_y.to_account_info())
+        };
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.auth.to_account_info()
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, amount)
+    }
+}
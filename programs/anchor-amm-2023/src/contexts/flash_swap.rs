@@ -0,0 +1,223 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, transfer};
+use anchor_spl::associated_token::AssociatedToken;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program::invoke;
+use crate::{assert_not_locked, assert_non_zero, assert_no_flash_loan};
+use crate::helpers::math::mul_wide;
+use crate::state::config::Config;
+use crate::state::observation::Observations;
+use crate::state::member::Member;
+use crate::errors::AmmError;
+use crate::helpers::access::{check_access, check_owner_not_blocked};
+use crate::helpers::token::init_user_token_accounts;
+use crate::helpers::pool::update_twap;
+
+#[derive(Accounts)]
+pub struct FlashSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    /// CHECK: Any X token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_x: UncheckedAccount<'info>,
+    /// CHECK: Any Y token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_y: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = auth
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = auth
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    /// CHECK: just a pda for signing
+    #[account(seeds = [b"auth"], bump = config.auth_bump)]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.bump
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+    /// CHECK: Arbitrary program called back to pay for the swap
+    #[account(executable)]
+    pub callback_program: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the user, which must not exist
+    #[account(
+        seeds = [b"blocked", config.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub blocked: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of user_x, checked in the handler
+    pub blocked_x: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of user_y, checked in the handler
+    pub blocked_y: UncheckedAccount<'info>,
+    /// Allowlist entry of the user, only checked in gated pools
+    pub member: Option<Box<Account<'info, Member>>>,
+    /// Gate token account of the user, only checked in gated pools
+    pub gate_token: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> FlashSwap<'info> {
+    // Remaining accounts are forwarded to the callback. The runtime only allows direct
+    // self recursion, so the callback can't re-enter this program while the swap is open.
+    pub fn flash_swap(
+        &mut self,
+        accounts: &[AccountInfo<'info>],
+        receive_x: bool,
+        amount_out: u64,
+        data: Vec<u8>
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        require!(!self.config.swaps_paused, AmmError::SwapsPaused);
+        assert_no_flash_loan!(self.config.flash_loan_amount);
+        assert_non_zero!([amount_out]);
+        require!(self.config.oracle.is_none(), AmmError::OraclePool);
+        require!(self.blocked.data_is_empty(), AmmError::WalletBlocked);
+        check_access(&self.config, &self.user.key(), self.member.as_deref(), self.gate_token.as_deref(), true)?;
+        self.init_user_accounts()?;
+        self.check_blocklist()?;
+
+        let (x0, y0) = (self.vault_x.amount, self.vault_y.amount);
+        let (out_x, out_y) = match receive_x {
+            true => (amount_out, 0),
+            false => (0, amount_out)
+        };
+        require!(out_x < x0 && out_y < y0, AmmError::InsufficientBalance);
+
+        // Send the output first, then let the caller pay for it
+        self.withdraw_token(receive_x, amount_out)?;
+        self.callback(accounts, data)?;
+
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+        let (x1, y1) = (self.vault_x.amount, self.vault_y.amount);
+        require!(
+            self.config.check_price_band(x0, y0, x1, y1, Clock::get()?.unix_timestamp),
+            AmmError::CircuitBreaker
+        );
+        // Only what's left missing from the vaults after the callback counts as an outflow
+        self.config.record_outflow(x0, y0, x0.saturating_sub(x1), y0.saturating_sub(y1), Clock::get()?.slot)?;
+        self.check_invariant(x0, y0, out_x, out_y)?;
+        update_twap(&mut self.config, &mut self.observations, &mut self.vault_x, &mut self.vault_y)
+    }
+
+    // Owners of the user's token accounts can't be blocked either
+    pub fn check_blocklist(
+        &self
+    ) -> Result<()> {
+        let pool = self.config.key();
+        check_owner_not_blocked(&pool, &self.user_x, &self.blocked_x)?;
+        check_owner_not_blocked(&pool, &self.user_y, &self.blocked_y)
+    }
+
+    pub fn init_user_accounts(
+        &self
+    ) -> Result<()> {
+        init_user_token_accounts(
+            [
+                (self.user_x.to_account_info(), self.mint_x.to_account_info()),
+                (self.user_y.to_account_info(), self.mint_y.to_account_info())
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info(),
+            self.associated_token_program.to_account_info(),
+            self.system_program.to_account_info()
+        )?;
+        Ok(())
+    }
+
+    pub fn callback(
+        &self,
+        accounts: &[AccountInfo<'info>],
+        data: Vec<u8>
+    ) -> Result<()> {
+        let ix = Instruction {
+            program_id: self.callback_program.key(),
+            accounts: accounts.iter().map(|a| match a.is_writable {
+                true => AccountMeta::new(a.key(), a.is_signer),
+                false => AccountMeta::new_readonly(a.key(), a.is_signer)
+            }).collect(),
+            data
+        };
+        let mut infos = accounts.to_vec();
+        infos.push(self.callback_program.to_account_info());
+        invoke(&ix, &infos).map_err(Into::into)
+    }
+
+    // (x1 - fee * x_in) * (y1 - fee * y_in) >= x0 * y0
+    pub fn check_invariant(
+        &self,
+        x0: u64,
+        y0: u64,
+        out_x: u64,
+        out_y: u64
+    ) -> Result<()> {
+        let (x1, y1) = (self.vault_x.amount as u128, self.vault_y.amount as u128);
+        let x_in = x1.saturating_sub(x0 as u128 - out_x as u128);
+        let y_in = y1.saturating_sub(y0 as u128 - out_y as u128);
+
+        let fee = self.config.fee as u128;
+        let x_adjusted = x1 * 10000 - x_in * fee;
+        let y_adjusted = y1 * 10000 - y_in * fee;
+
+        require!(
+            mul_wide(x_adjusted, y_adjusted) >= mul_wide(x0 as u128 * y0 as u128, 10000 * 10000),
+            AmmError::InvariantViolated
+        );
+        Ok(())
+    }
+
+    pub fn withdraw_token(
+        &mut self,
+        receive_x: bool,
+        amount: u64
+    ) -> Result<()> {
+        let (from, to) = match receive_x {
+            true => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.user_y.to_account_info())
+        };
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.auth.to_account_info()
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, amount)
+    }
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, transfer};
+use crate::assert_non_zero;
+use crate::state::farm::Farm;
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct FundRewardStream<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"farm", farm.config.as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        constraint = user_reward.owner == user.key() || user_reward.delegate == COption::Some(user.key()) @ AmmError::InvalidTokenOwner
+    )]
+    pub user_reward: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm
+    )]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>
+}
+
+impl<'info> FundRewardStream<'info> {
+    // Anyone can fund a stream. Emissions stop once a stream runs out of funding.
+    pub fn fund_reward_stream(
+        &mut self,
+        amount: u64
+    ) -> Result<()> {
+        assert_non_zero!([amount]);
+        let i = self.farm.stream_index(self.reward_mint.key())?;
+        // Emissions that were skipped while unfunded are not paid retroactively
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        let stream = &mut self.farm.streams[i];
+        stream.funded = stream.funded.checked_add(amount).ok_or(AmmError::Overflow)?;
+
+        let accounts = Transfer {
+            from: self.user_reward.to_account_info(),
+            to: self.reward_vault.to_account_info(),
+            authority: self.user.to_account_info()
+        };
+        transfer(CpiContext::new(self.token_program.to_account_info(), accounts), amount)
+    }
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::config::Config;
+use crate::state::observation::Observations;
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+#[instruction(cardinality: u16)]
+pub struct IncreaseObservationCardinality<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.bump,
+        realloc = Observations::len(cardinality),
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> IncreaseObservationCardinality<'info> {
+    // Anyone can pay for more history. Each call can grow the account by at most 10KiB.
+    pub fn increase_observation_cardinality(
+        &mut self,
+        cardinality: u16
+    ) -> Result<()> {
+        require!(cardinality > self.observations.cardinality, AmmError::InvalidAmount);
+        self.observations.cardinality = cardinality;
+        Ok(())
+    }
+}
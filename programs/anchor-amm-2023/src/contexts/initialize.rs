@@ -3,8 +3,12 @@ use std::collections::BTreeMap;
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token, TokenAccount};
 use anchor_spl::associated_token::AssociatedToken;
-use crate::errors::AmmError;
+use anchor_spl::metadata::Metadata;
 use crate::state::config::Config;
+use crate::state::observation::Observations;
+use crate::state::lp_metadata::LpMetadata;
+use crate::helpers::metadata::LpMetadataAccounts;
+use crate::helpers::pool::PoolInit;
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -36,6 +40,13 @@ pub struct Initialize<'info> {
         associated_token::authority = auth,
     )]
     pub vault_y: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_lp,
+        associated_token::authority = auth,
+    )]
+    pub vault_lp: Box<Account<'info, TokenAccount>>,
     /// CHECK: This is safe because it's just used to sign
     #[account(seeds = [b"auth"], bump)]
     pub auth: UncheckedAccount<'info>,
@@ -47,6 +58,19 @@ pub struct Initialize<'info> {
         space = Config::LEN
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"observations", config.key().as_ref()],
+        bump,
+        space = Observations::len(1)
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+    /// CHECK: Metaplex metadata PDA of the LP mint, checked by the metadata program
+    #[account(mut)]
+    pub lp_metadata: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    pub rent: Option<Sysvar<'info, Rent>>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>
@@ -58,26 +82,23 @@ impl<'info> Initialize<'info> {
         bumps: &BTreeMap<String, u8>,
         seed: u64,
         fee: u16,
-        authority: Option<Pubkey>        
+        authority: Option<Pubkey>,
+        metadata: Option<LpMetadata>
     ) -> Result<()> {
-        // Don't charge >100.00% as a fee
-        require!(fee <= 10000, AmmError::InvalidFee);
-
-        let (auth_bump, config_bump, lp_bump) = (
-            *bumps.get("auth").ok_or(AmmError::BumpError)?,
-            *bumps.get("config").ok_or(AmmError::BumpError)?,
-            *bumps.get("mint_lp").ok_or(AmmError::BumpError)?
-        );
-        self.config.init(
-            seed,
-            authority,
-            self.mint_x.key(),
-            self.mint_y.key(),
-            fee,
-            auth_bump,
-            config_bump,
-            lp_bump
-        );
-        Ok(())
+        PoolInit {
+            config: &mut self.config,
+            observations: &mut self.observations,
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            accounts: LpMetadataAccounts {
+                metadata: self.lp_metadata.as_ref().map(|a| a.to_account_info()),
+                token_metadata_program: self.token_metadata_program.as_ref().map(|p| p.to_account_info()),
+                rent: self.rent.as_ref().map(|r| r.to_account_info()),
+                mint: self.mint_lp.to_account_info(),
+                auth: self.auth.to_account_info(),
+                payer: self.initializer.to_account_info(),
+                system_program: self.system_program.to_account_info()
+            }
+        }.init(bumps, seed, fee, authority, metadata)
     }
 }
\ No newline at end of file
@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, MintTo, transfer, mint_to};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use crate::assert_non_zero;
+use crate::constants::MINIMUM_LIQUIDITY;
+use crate::errors::AmmError;
+use crate::helpers::math::initial_liquidity;
+use crate::state::config::Config;
+use crate::state::observation::Observations;
+use crate::state::lp_metadata::LpMetadata;
+use crate::helpers::metadata::LpMetadataAccounts;
+use crate::helpers::pool::PoolInit;
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct InitializeWithLiquidity<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+        init,
+        seeds = [b"lp", config.key().as_ref()],
+        payer = initializer,
+        bump,
+        mint::decimals = 6,
+        mint::authority = auth
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_x,
+        associated_token::authority = auth,
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_y,
+        associated_token::authority = auth,
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_lp,
+        associated_token::authority = auth,
+    )]
+    pub vault_lp: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_x,
+        constraint = initializer_x.owner == initializer.key() || initializer_x.delegate == COption::Some(initializer.key()) @ AmmError::InvalidTokenOwner
+    )]
+    pub initializer_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_y,
+        constraint = initializer_y.owner == initializer.key() || initializer_y.delegate == COption::Some(initializer.key()) @ AmmError::InvalidTokenOwner
+    )]
+    pub initializer_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_lp,
+        associated_token::authority = initializer,
+    )]
+    pub initializer_lp: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This is safe because it's just used to sign
+    #[account(seeds = [b"auth"], bump)]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"config", seed.to_le_bytes().as_ref()],
+        bump,
+        space = Config::LEN
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"observations", config.key().as_ref()],
+        bump,
+        space = Observations::len(1)
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+    /// CHECK: Metaplex metadata PDA of the LP mint, checked by the metadata program
+    #[account(mut)]
+    pub lp_metadata: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    pub rent: Option<Sysvar<'info, Rent>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> InitializeWithLiquidity<'info> {
+    pub fn init(
+        &mut self,
+        bumps: &BTreeMap<String, u8>,
+        seed: u64,
+        fee: u16,
+        authority: Option<Pubkey>,
+        amount_x: u64,
+        amount_y: u64,
+        metadata: Option<LpMetadata>
+    ) -> Result<()> {
+        assert_non_zero!([amount_x, amount_y]);
+        PoolInit {
+            config: &mut self.config,
+            observations: &mut self.observations,
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            accounts: LpMetadataAccounts {
+                metadata: self.lp_metadata.as_ref().map(|a| a.to_account_info()),
+                token_metadata_program: self.token_metadata_program.as_ref().map(|p| p.to_account_info()),
+                rent: self.rent.as_ref().map(|r| r.to_account_info()),
+                mint: self.mint_lp.to_account_info(),
+                auth: self.auth.to_account_info(),
+                payer: self.initializer.to_account_info(),
+                system_program: self.system_program.to_account_info()
+            }
+        }.init(bumps, seed, fee, authority, metadata)?;
+
+        let liquidity = initial_liquidity(amount_x, amount_y)?;
+
+        self.deposit_tokens(true, amount_x)?;
+        self.deposit_tokens(false, amount_y)?;
+        let observation = self.config.update_twap(amount_x, amount_y, Clock::get()?.unix_timestamp);
+        self.observations.write(observation);
+        self.mint_lp_tokens(self.vault_lp.to_account_info(), MINIMUM_LIQUIDITY)?;
+        self.mint_lp_tokens(self.initializer_lp.to_account_info(), liquidity - MINIMUM_LIQUIDITY)
+    }
+
+    pub fn deposit_tokens(
+        &self,
+        is_x: bool,
+        amount: u64
+    ) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.initializer_x.to_account_info(), self.vault_x.to_account_info()),
+            false => (self.initializer_y.to_account_info(), self.vault_y.to_account_info())
+        };
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.initializer.to_account_info(),
+        };
+        let ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer(ctx, amount)
+    }
+
+    pub fn mint_lp_tokens(
+        &self,
+        to: AccountInfo<'info>,
+        amount: u64
+    ) -> Result<()> {
+        let accounts = MintTo {
+            mint: self.mint_lp.to_account_info(),
+            to,
+            authority: self.auth.to_account_info(),
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+        mint_to(ctx, amount)
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, Burn, transfer, burn};
+use anchor_spl::associated_token::AssociatedToken;
+use constant_product_curve::ConstantProduct;
+use crate::{assert_non_zero, assert_not_locked, assert_no_flash_loan};
+use crate::state::config::Config;
+use crate::state::observation::Observations;
+use crate::state::lp_lock::LpLock;
+use crate::errors::AmmError;
+use crate::helpers::token::init_user_token_accounts;
+use crate::helpers::pool::update_twap;
+
+#[derive(Accounts)]
+pub struct LockLp<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = auth,
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = auth,
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Any X token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_x: UncheckedAccount<'info>,
+    /// CHECK: Any Y token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_y: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = mint_lp,
+        constraint = user_lp.owner == user.key() || user_lp.delegate == COption::Some(user.key()) @ AmmError::InvalidTokenOwner
+    )]
+    pub user_lp: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"lock", config.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = LpLock::LEN
+    )]
+    pub lp_lock: Box<Account<'info, LpLock>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = lp_lock
+    )]
+    pub lock_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: just a pda for signing
+    #[account(seeds = [b"auth"], bump = config.auth_bump)]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.bump
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> LockLp<'info> {
+    pub fn lock_lp(
+        &mut self,
+        bumps: &BTreeMap<String, u8>,
+        amount: u64,
+        unlock_ts: i64
+    ) -> Result<()> {
+        // A flash loan would deflate the reserves the lock's fee baseline is taken from
+        assert_not_locked!(self.config.locked);
+        assert_no_flash_loan!(self.config.flash_loan_amount);
+        assert_non_zero!([amount]);
+        if self.lp_lock.owner == Pubkey::default() {
+            self.lp_lock.owner = self.user.key();
+            self.lp_lock.config = self.config.key();
+            self.lp_lock.bump = *bumps.get("lp_lock").ok_or(AmmError::BumpError)?;
+        }
+        // Locks can be extended but never shortened
+        require!(unlock_ts >= self.lp_lock.unlock_ts && unlock_ts > Clock::get()?.unix_timestamp, AmmError::InvalidUnlockTime);
+        self.lp_lock.unlock_ts = unlock_ts;
+
+        let liquidity_per_lp = LpLock::liquidity_per_lp(self.vault_x.amount, self.vault_y.amount, self.mint_lp.supply)?;
+        self.lp_lock.add(amount, liquidity_per_lp)?;
+
+        let accounts = Transfer {
+            from: self.user_lp.to_account_info(),
+            to: self.lock_vault.to_account_info(),
+            authority: self.user.to_account_info()
+        };
+        transfer(CpiContext::new(self.token_program.to_account_info(), accounts), amount)
+    }
+
+    pub fn unlock_lp(
+        &mut self
+    ) -> Result<()> {
+        require!(Clock::get()?.unix_timestamp >= self.lp_lock.unlock_ts, AmmError::StillLocked);
+        let amount = self.lp_lock.amount;
+        assert_non_zero!([amount]);
+        self.lp_lock.amount = 0;
+
+        let accounts = Transfer {
+            from: self.lock_vault.to_account_info(),
+            to: self.user_lp.to_account_info(),
+            authority: self.lp_lock.to_account_info()
+        };
+        let config = self.config.key();
+        let user = self.user.key();
+        let seeds = &[
+            &b"lock"[..],
+            config.as_ref(),
+            user.as_ref(),
+            &[self.lp_lock.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+        transfer(ctx, amount)
+    }
+
+    // Withdraws the LP tokens that fees have added on top of the locked principal
+    pub fn claim_lock_fees(
+        &mut self,
+        min_x: u64, // Min amount of X we are willing to receive
+        min_y: u64 // Min amount of Y we are willing to receive
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        assert_no_flash_loan!(self.config.flash_loan_amount);
+        self.init_user_accounts()?;
+
+        let liquidity_per_lp = LpLock::liquidity_per_lp(self.vault_x.amount, self.vault_y.amount, self.mint_lp.supply)?;
+        let fee_lp = self.lp_lock.fee_lp(liquidity_per_lp)?;
+        assert_non_zero!([fee_lp]);
+
+        let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            fee_lp,
+            6
+        ).map_err(AmmError::from)?;
+
+        // Check for slippage
+        require!(min_x <= amounts.x && min_y <= amounts.y, AmmError::SlippageExceeded);
+        self.config.record_outflow(self.vault_x.amount, self.vault_y.amount, amounts.x, amounts.y, Clock::get()?.slot)?;
+
+        // What's left is worth the same liquidity as when it was locked
+        self.lp_lock.amount -= fee_lp;
+        self.lp_lock.liquidity_per_lp = liquidity_per_lp;
+
+        self.withdraw_tokens(true, amounts.x)?;
+        self.withdraw_tokens(false, amounts.y)?;
+        self.burn_lock_lp(fee_lp)?;
+        update_twap(&mut self.config, &mut self.observations, &mut self.vault_x, &mut self.vault_y)
+    }
+
+    pub fn init_user_accounts(
+        &self
+    ) -> Result<()> {
+        init_user_token_accounts(
+            [
+                (self.user_x.to_account_info(), self.mint_x.to_account_info()),
+                (self.user_y.to_account_info(), self.mint_y.to_account_info())
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info(),
+            self.associated_token_program.to_account_info(),
+            self.system_program.to_account_info()
+        )?;
+        Ok(())
+    }
+
+    pub fn withdraw_tokens(
+        &self,
+        is_x: bool,
+        amount: u64
+    ) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.user_y.to_account_info())
+        };
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.auth.to_account_info()
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, amount)
+    }
+
+    pub fn burn_lock_lp(
+        &self,
+        amount: u64
+    ) -> Result<()> {
+        let accounts = Burn {
+            mint: self.mint_lp.to_account_info(),
+            from: self.lock_vault.to_account_info(),
+            authority: self.lp_lock.to_account_info()
+        };
+
+        let config = self.config.key();
+        let user = self.user.key();
+        let seeds = &[
+            &b"lock"[..],
+            config.as_ref(),
+            user.as_ref(),
+            &[self.lp_lock.bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        burn(ctx, amount)
+    }
+}
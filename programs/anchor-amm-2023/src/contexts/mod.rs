@@ -1,10 +1,78 @@
 pub mod initialize;
+pub mod initialize_with_liquidity;
 pub mod deposit;
 pub mod withdraw;
 pub mod swap;
+pub mod trip_circuit_breaker;
 pub mod update;
+pub mod update_lp_metadata;
+pub mod add_member;
+pub mod remove_member;
+pub mod block_wallet;
+pub mod unblock_wallet;
+pub mod queue_action;
+pub mod execute_action;
+pub mod cancel_action;
+pub mod flash;
+pub mod flash_swap;
+pub mod observe;
+pub mod increase_observation_cardinality;
+pub mod initialize_farm;
+pub mod stake;
+pub mod add_reward_stream;
+pub mod fund_reward_stream;
+pub mod update_farm;
+pub mod lock_lp;
+pub mod deposit_position;
+pub mod withdraw_position;
+pub mod multi_initialize;
+pub mod multi_deposit;
+pub mod multi_withdraw;
+pub mod multi_swap;
+pub mod multi_update;
+pub mod multi_add_member;
+pub mod multi_remove_member;
+pub mod multi_block_wallet;
+pub mod multi_unblock_wallet;
+pub mod multi_queue_action;
+pub mod multi_execute_action;
+pub mod multi_cancel_action;
 pub use initialize::*;
+pub use initialize_with_liquidity::*;
 pub use deposit::*;
 pub use withdraw::*;
 pub use swap::*;
-pub use update::*;
\ No newline at end of file
+pub use trip_circuit_breaker::*;
+pub use update::*;
+pub use update_lp_metadata::*;
+pub use add_member::*;
+pub use remove_member::*;
+pub use block_wallet::*;
+pub use unblock_wallet::*;
+pub use queue_action::*;
+pub use execute_action::*;
+pub use cancel_action::*;
+pub use flash::*;
+pub use flash_swap::*;
+pub use observe::*;
+pub use increase_observation_cardinality::*;
+pub use initialize_farm::*;
+pub use stake::*;
+pub use add_reward_stream::*;
+pub use fund_reward_stream::*;
+pub use update_farm::*;
+pub use lock_lp::*;
+pub use deposit_position::*;
+pub use withdraw_position::*;
+pub use multi_initialize::*;
+pub use multi_deposit::*;
+pub use multi_withdraw::*;
+pub use multi_swap::*;
+pub use multi_update::*;
+pub use multi_add_member::*;
+pub use multi_remove_member::*;
+pub use multi_block_wallet::*;
+pub use multi_unblock_wallet::*;
+pub use multi_queue_action::*;
+pub use multi_execute_action::*;
+pub use multi_cancel_action::*;
\ No newline at end of file
@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use crate::state::multi_pool::MultiPool;
+use crate::state::admin_action::{AdminAction, AdminQueue};
+use crate::state::member::Member;
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct MultiAddMember<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"member", pool.key().as_ref(), wallet.as_ref()],
+        bump,
+        space = Member::LEN
+    )]
+    pub member: Account<'info, Member>,
+    /// Queue holding the action, needed once the pool has a timelock
+    #[account(
+        mut,
+        seeds = [b"admin_queue", pool.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Option<Box<Account<'info, AdminQueue>>>,
+    #[account(
+        seeds = [
+            b"multi",
+            pool.seed.to_le_bytes().as_ref()
+        ],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> MultiAddMember<'info> {
+    pub fn add_member(
+        &mut self,
+        bumps: &BTreeMap<String, u8>,
+        wallet: Pubkey
+    ) -> Result<()> {
+        AdminAction::AddMember { wallet }.authorize_multi(&self.pool, self.user.key(), self.admin_queue.as_deref_mut())?;
+        self.member.init(
+            self.pool.key(),
+            wallet,
+            *bumps.get("member").ok_or(AmmError::BumpError)?
+        );
+        Ok(())
+    }
+}
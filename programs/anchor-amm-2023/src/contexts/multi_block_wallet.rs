@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use crate::state::multi_pool::MultiPool;
+use crate::state::admin_action::{AdminAction, AdminQueue};
+use crate::state::blocked::Blocked;
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct MultiBlockWallet<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"blocked", pool.key().as_ref(), wallet.as_ref()],
+        bump,
+        space = Blocked::LEN
+    )]
+    pub blocked: Account<'info, Blocked>,
+    /// Queue holding the action, needed once the pool has a timelock
+    #[account(
+        mut,
+        seeds = [b"admin_queue", pool.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Option<Box<Account<'info, AdminQueue>>>,
+    #[account(
+        seeds = [
+            b"multi",
+            pool.seed.to_le_bytes().as_ref()
+        ],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> MultiBlockWallet<'info> {
+    pub fn block_wallet(
+        &mut self,
+        bumps: &BTreeMap<String, u8>,
+        wallet: Pubkey
+    ) -> Result<()> {
+        AdminAction::BlockWallet { wallet }.authorize_multi(&self.pool, self.user.key(), self.admin_queue.as_deref_mut())?;
+        self.blocked.init(
+            self.pool.key(),
+            wallet,
+            *bumps.get("blocked").ok_or(AmmError::BumpError)?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::state::multi_pool::MultiPool;
+use crate::state::role::Role;
+use crate::state::admin_action::AdminQueue;
+
+#[derive(Accounts)]
+pub struct MultiCancelAction<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"admin_queue", pool.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Box<Account<'info, AdminQueue>>,
+    #[account(
+        seeds = [
+            b"multi",
+            pool.seed.to_le_bytes().as_ref()
+        ],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>
+}
+
+impl<'info> MultiCancelAction<'info> {
+    // Guardians can veto pending changes as well as pause
+    pub fn cancel_action(
+        &mut self,
+        id: u64
+    ) -> Result<()> {
+        self.pool.check_role(Role::Guardian, self.user.key())?;
+        self.admin_queue.remove(id)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, MintTo, transfer, mint_to};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::{assert_non_zero, assert_not_locked, assert_not_expired};
+use crate::constants::MINIMUM_LIQUIDITY;
+use crate::state::multi_pool::MultiPool;
+use crate::state::member::Member;
+use crate::errors::AmmError;
+use crate::helpers::access::{check_access, check_owner_not_blocked};
+use crate::helpers::token::init_user_token_account;
+
+#[derive(Accounts)]
+pub struct MultiDeposit<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"multi_lp", pool.key().as_ref()],
+        bump = pool.lp_bump
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    /// CHECK: Any LP token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_lp: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = pool,
+    )]
+    pub pool_lp: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [
+            b"multi",
+            pool.seed.to_le_bytes().as_ref()
+        ],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>,
+    /// CHECK: Blocklist entry of the user, which must not exist
+    #[account(
+        seeds = [b"blocked", pool.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub blocked: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of user_lp, checked in the handler
+    pub blocked_lp: UncheckedAccount<'info>,
+    /// Allowlist entry of the user, only checked in gated pools
+    pub member: Option<Box<Account<'info, Member>>>,
+    /// Gate token account of the user, only checked in gated pools
+    pub gate_token: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MultiDeposit<'info> {
+    // Remaining accounts: the pool's vaults, the user's token account for each asset, then the
+    // blocklist entry of each of those accounts' owners
+    pub fn deposit(
+        &self,
+        accounts: &[AccountInfo<'info>],
+        amount: u64, // Amount of LP token to claim, or the minimum to claim on the first deposit
+        max_amounts: Vec<u64>, // Max amount of each asset we are willing to deposit
+        expiration: i64,
+    ) -> Result<()> {
+        assert_not_locked!(self.pool.locked);
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount]);
+        require!(self.blocked.data_is_empty(), AmmError::WalletBlocked);
+        check_access(&self.pool, &self.user.key(), self.member.as_deref(), self.gate_token.as_deref(), false)?;
+        self.init_user_accounts()?;
+
+        let n = self.pool.mints.len();
+        require!(max_amounts.len() == n && accounts.len() == n * 3, AmmError::InvalidAssetCount);
+        self.check_blocklist(&accounts[n..n * 2], &accounts[n * 2..])?;
+
+        let balances = self.pool.load_vaults(accounts)?;
+        let supply = self.mint_lp.supply;
+
+        // Balances donated before the first deposit are ignored and go to the first depositor
+        let amounts = match supply == 0 {
+            true => {
+                assert_non_zero!(max_amounts);
+                max_amounts.clone()
+            },
+            false => {
+                // Round up in the pool's favour
+                balances.iter().map(|b| {
+                    let a = (b.amount as u128).checked_mul(amount as u128).ok_or(AmmError::Overflow)?;
+                    u64::try_from(a.div_ceil(supply as u128)).map_err(|_| error!(AmmError::Overflow))
+                }).collect::<Result<Vec<u64>>>()?
+            }
+        };
+
+        // Check for slippage
+        require!(amounts.iter().zip(max_amounts.iter()).all(|(a, max)| a <= max), AmmError::SlippageExceeded);
+
+        for (i, a) in amounts.iter().enumerate() {
+            self.deposit_tokens(accounts[n + i].clone(), accounts[i].clone(), *a)?;
+        }
+
+        if supply > 0 {
+            return self.mint_lp_tokens(self.user_lp.to_account_info(), amount);
+        }
+
+        // Initial liquidity is the weighted geometric mean of the deposit, part of which is locked forever
+        let liquidity = self.pool.invariant(&amounts)?;
+        require!(liquidity > MINIMUM_LIQUIDITY, AmmError::LiquidityLessThanMinimum);
+        require!(liquidity - MINIMUM_LIQUIDITY >= amount, AmmError::SlippageExceeded);
+        self.mint_lp_tokens(self.pool_lp.to_account_info(), MINIMUM_LIQUIDITY)?;
+        self.mint_lp_tokens(self.user_lp.to_account_info(), liquidity - MINIMUM_LIQUIDITY)
+    }
+
+    // Remaining accounts: the pool's vaults, the user's token account for the deposited asset, then
+    // the blocklist entry of its owner
+    pub fn deposit_single(
+        &self,
+        accounts: &[AccountInfo<'info>],
+        index: u8,
+        amount: u64, // Amount of the asset to deposit
+        min_lp: u64, // Min amount of LP token we are willing to claim
+        expiration: i64,
+    ) -> Result<()> {
+        assert_not_locked!(self.pool.locked);
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount]);
+        require!(self.blocked.data_is_empty(), AmmError::WalletBlocked);
+        check_access(&self.pool, &self.user.key(), self.member.as_deref(), self.gate_token.as_deref(), false)?;
+        self.init_user_accounts()?;
+
+        let n = self.pool.mints.len();
+        let i = self.pool.check_index(index)?;
+        require!(accounts.len() == n + 2, AmmError::InvalidAssetCount);
+        require!(self.mint_lp.supply > 0, AmmError::NoLiquidityInPool);
+        self.check_blocklist(&accounts[n..n + 1], &accounts[n + 1..])?;
+
+        let balances = self.pool.load_vaults(accounts)?;
+        let lp = self.pool.single_deposit_lp(i, balances[i].amount, self.mint_lp.supply, amount)?;
+
+        // Check for slippage
+        require!(lp >= min_lp, AmmError::SlippageExceeded);
+        assert_non_zero!([lp]);
+
+        self.deposit_tokens(accounts[n].clone(), accounts[i].clone(), amount)?;
+        self.mint_lp_tokens(self.user_lp.to_account_info(), lp)
+    }
+
+    // Owners of the token accounts paying in and of the LP account can't be blocked either
+    pub fn check_blocklist(
+        &self,
+        user_accounts: &[AccountInfo<'info>],
+        entries: &[AccountInfo<'info>]
+    ) -> Result<()> {
+        let pool = self.pool.key();
+        for (account, blocked) in user_accounts.iter().zip(entries.iter()) {
+            check_owner_not_blocked(&pool, account, blocked)?;
+        }
+        check_owner_not_blocked(&pool, &self.user_lp, &self.blocked_lp)
+    }
+
+    // Create the user's LP ATA if needed and check the LP account belongs to them
+    pub fn init_user_accounts(
+        &self
+    ) -> Result<()> {
+        init_user_token_account(
+            self.user_lp.to_account_info(),
+            self.mint_lp.to_account_info(),
+            self.user.to_account_info(),
+            self.token_program.to_account_info(),
+            self.associated_token_program.to_account_info(),
+            self.system_program.to_account_info()
+        )
+    }
+
+    pub fn deposit_tokens(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        amount: u64
+    ) -> Result<()> {
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+        };
+        let ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer(ctx, amount)
+    }
+
+    pub fn mint_lp_tokens(
+        &self,
+        to: AccountInfo<'info>,
+        amount: u64
+    ) -> Result<()> {
+        let accounts = MintTo {
+            mint: self.mint_lp.to_account_info(),
+            to,
+            authority: self.pool.to_account_info(),
+        };
+
+        let seed = self.pool.seed.to_le_bytes();
+        let seeds = &[
+            &b"multi"[..],
+            seed.as_ref(),
+            &[self.pool.pool_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+        mint_to(ctx, amount)
+    }
+}
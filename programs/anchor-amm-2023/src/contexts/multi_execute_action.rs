@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::multi_pool::MultiPool;
+use crate::state::admin_action::{AdminAction, AdminQueue};
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct MultiExecuteAction<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"admin_queue", pool.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Box<Account<'info, AdminQueue>>,
+    #[account(
+        mut,
+        seeds = [
+            b"multi",
+            pool.seed.to_le_bytes().as_ref()
+        ],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>
+}
+
+impl<'info> MultiExecuteAction<'info> {
+    pub fn execute_action(
+        &mut self,
+        id: u64
+    ) -> Result<()> {
+        let queued = self.admin_queue.remove(id)?;
+        require!(Clock::get()?.unix_timestamp >= queued.eta, AmmError::ActionNotReady);
+
+        let action = AdminAction::try_from_slice(&queued.data).map_err(|_| AmmError::InvalidAction)?;
+        self.pool.check_role(action.role(), self.user.key())?;
+        action.apply_multi(&mut self.pool)
+    }
+}
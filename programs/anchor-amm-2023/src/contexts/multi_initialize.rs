@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::associated_token::{AssociatedToken, get_associated_token_address};
+use crate::constants::*;
+use crate::errors::AmmError;
+use crate::state::multi_pool::MultiPool;
+
+#[derive(Accounts)]
+#[instruction(seed: u64, fee: u16, authority: Option<Pubkey>, weights: Vec<u16>)]
+pub struct MultiInitialize<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        init,
+        seeds = [b"multi_lp", pool.key().as_ref()],
+        payer = initializer,
+        bump,
+        mint::decimals = 6,
+        mint::authority = pool
+    )]
+    pub mint_lp: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"multi", seed.to_le_bytes().as_ref()],
+        bump,
+        space = MultiPool::len(weights.len())
+    )]
+    pub pool: Account<'info, MultiPool>,
+    // Holds the minimum liquidity locked by the first deposit
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_lp,
+        associated_token::authority = pool,
+    )]
+    pub pool_lp: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> MultiInitialize<'info> {
+    // Vaults are passed in as remaining accounts: one ATA owned by the pool per asset
+    pub fn init(
+        &mut self,
+        bumps: &BTreeMap<String, u8>,
+        vaults: &[AccountInfo<'info>],
+        seed: u64,
+        fee: u16,
+        authority: Option<Pubkey>,
+        weights: Vec<u16>
+    ) -> Result<()> {
+        // Don't charge >100.00% as a fee
+        require!(fee <= 10000, AmmError::InvalidFee);
+        require!(weights.len() >= MIN_ASSETS && weights.len() <= MAX_ASSETS, AmmError::InvalidAssetCount);
+        require!(vaults.len() == weights.len(), AmmError::InvalidAssetCount);
+        require!(
+            weights.iter().all(|w| *w >= MIN_WEIGHT) && weights.iter().map(|w| *w as u32).sum::<u32>() == 10000,
+            AmmError::InvalidWeights
+        );
+
+        let mut mints: Vec<Pubkey> = Vec::with_capacity(vaults.len());
+        for info in vaults {
+            let vault = Account::<TokenAccount>::try_from(info)?;
+            require!(!mints.contains(&vault.mint), AmmError::InvalidToken);
+            require_keys_eq!(vault.owner, self.pool.key(), AmmError::InvalidVault);
+            require_keys_eq!(
+                info.key(),
+                get_associated_token_address(&self.pool.key(), &vault.mint),
+                AmmError::InvalidVault
+            );
+            mints.push(vault.mint);
+        }
+
+        let (pool_bump, lp_bump) = (
+            *bumps.get("pool").ok_or(AmmError::BumpError)?,
+            *bumps.get("mint_lp").ok_or(AmmError::BumpError)?
+        );
+        self.pool.init(
+            seed,
+            authority,
+            fee,
+            mints,
+            vaults.iter().map(|v| v.key()).collect(),
+            weights,
+            pool_bump,
+            lp_bump
+        );
+        Ok(())
+    }
+}
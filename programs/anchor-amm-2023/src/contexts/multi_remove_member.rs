@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::multi_pool::MultiPool;
+use crate::state::admin_action::{AdminAction, AdminQueue};
+use crate::state::member::Member;
+
+#[derive(Accounts)]
+pub struct MultiRemoveMember<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"member", pool.key().as_ref(), member.wallet.as_ref()],
+        bump = member.bump
+    )]
+    pub member: Account<'info, Member>,
+    /// Queue holding the action, needed once the pool has a timelock
+    #[account(
+        mut,
+        seeds = [b"admin_queue", pool.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Option<Box<Account<'info, AdminQueue>>>,
+    #[account(
+        seeds = [
+            b"multi",
+            pool.seed.to_le_bytes().as_ref()
+        ],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>
+}
+
+impl<'info> MultiRemoveMember<'info> {
+    pub fn remove_member(
+        &mut self
+    ) -> Result<()> {
+        let wallet = self.member.wallet;
+        AdminAction::RemoveMember { wallet }.authorize_multi(&self.pool, self.user.key(), self.admin_queue.as_deref_mut())
+    }
+}
@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{Token, TokenAccount, Transfer, transfer};
+use crate::{assert_not_locked, assert_not_expired, assert_non_zero};
+use crate::state::multi_pool::MultiPool;
+use crate::state::member::Member;
+use crate::errors::AmmError;
+use crate::helpers::access::{check_access, check_owner_not_blocked};
+
+#[derive(Accounts)]
+pub struct MultiSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        constraint = user_in.owner == user.key() || user_in.delegate == COption::Some(user.key()) @ AmmError::InvalidTokenOwner
+    )]
+    pub user_in: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub user_out: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [
+            b"multi",
+            pool.seed.to_le_bytes().as_ref()
+        ],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>,
+    /// CHECK: Blocklist entry of the user, which must not exist
+    #[account(
+        seeds = [b"blocked", pool.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub blocked: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of user_in, checked in the handler
+    pub blocked_in: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of user_out, checked in the handler
+    pub blocked_out: UncheckedAccount<'info>,
+    /// Allowlist entry of the user, only checked in gated pools
+    pub member: Option<Box<Account<'info, Member>>>,
+    /// Gate token account of the user, only checked in gated pools
+    pub gate_token: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Program<'info, Token>
+}
+
+impl<'info> MultiSwap<'info> {
+    // Vaults are passed in as remaining accounts, in the order stored on the pool
+    pub fn swap(
+        &mut self,
+        vaults: &[AccountInfo<'info>],
+        in_index: u8,
+        out_index: u8,
+        amount: u64,
+        min: u64,
+        expiration: i64
+    ) -> Result<()> {
+        assert_not_locked!(self.pool.locked);
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount]);
+        require!(self.blocked.data_is_empty(), AmmError::WalletBlocked);
+        check_access(&self.pool, &self.user.key(), self.member.as_deref(), self.gate_token.as_deref(), true)?;
+        self.check_blocklist()?;
+
+        let (i, o) = (self.pool.check_index(in_index)?, self.pool.check_index(out_index)?);
+        require!(i != o, AmmError::InvalidIndex);
+        require_keys_eq!(self.user_in.mint, self.pool.mints[i], AmmError::InvalidToken);
+        require_keys_eq!(self.user_out.mint, self.pool.mints[o], AmmError::InvalidToken);
+
+        let balances = self.pool.load_vaults(vaults)?;
+        let out = self.pool.swap_out(i, o, balances[i].amount, balances[o].amount, amount)?;
+
+        // Check for slippage
+        require!(out >= min, AmmError::SlippageExceeded);
+        assert_non_zero!([out]);
+
+        self.deposit_token(vaults[i].clone(), amount)?;
+        self.withdraw_token(vaults[o].clone(), out)
+    }
+
+    // Neither the owner paying in nor the recipient can be blocked
+    pub fn check_blocklist(
+        &self
+    ) -> Result<()> {
+        let pool = self.pool.key();
+        check_owner_not_blocked(&pool, &self.user_in.to_account_info(), &self.blocked_in)?;
+        check_owner_not_blocked(&pool, &self.user_out.to_account_info(), &self.blocked_out)
+    }
+
+    pub fn deposit_token(
+        &self,
+        vault: AccountInfo<'info>,
+        amount: u64
+    ) -> Result<()> {
+        let accounts = Transfer {
+            from: self.user_in.to_account_info(),
+            to: vault,
+            authority: self.user.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            accounts
+        );
+
+        transfer(ctx, amount)
+    }
+
+    pub fn withdraw_token(
+        &self,
+        vault: AccountInfo<'info>,
+        amount: u64
+    ) -> Result<()> {
+        let accounts = Transfer {
+            from: vault,
+            to: self.user_out.to_account_info(),
+            authority: self.pool.to_account_info()
+        };
+
+        let seed = self.pool.seed.to_le_bytes();
+        let seeds = &[
+            &b"multi"[..],
+            seed.as_ref(),
+            &[self.pool.pool_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, amount)
+    }
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::multi_pool::MultiPool;
+use crate::state::admin_action::{AdminAction, AdminQueue};
+use crate::state::blocked::Blocked;
+
+#[derive(Accounts)]
+pub struct MultiUnblockWallet<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"blocked", pool.key().as_ref(), blocked.wallet.as_ref()],
+        bump = blocked.bump
+    )]
+    pub blocked: Account<'info, Blocked>,
+    /// Queue holding the action, needed once the pool has a timelock
+    #[account(
+        mut,
+        seeds = [b"admin_queue", pool.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Option<Box<Account<'info, AdminQueue>>>,
+    #[account(
+        seeds = [
+            b"multi",
+            pool.seed.to_le_bytes().as_ref()
+        ],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>
+}
+
+impl<'info> MultiUnblockWallet<'info> {
+    pub fn unblock_wallet(
+        &mut self
+    ) -> Result<()> {
+        let wallet = self.blocked.wallet;
+        AdminAction::UnblockWallet { wallet }.authorize_multi(&self.pool, self.user.key(), self.admin_queue.as_deref_mut())
+    }
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use crate::state::multi_pool::MultiPool;
+use crate::state::member::AccessMode;
+use crate::state::role::Role;
+use crate::state::admin_action::AdminAction;
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct MultiUpdate<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"multi",
+            pool.seed.to_le_bytes().as_ref()
+        ],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>
+}
+
+impl<'info> MultiUpdate<'info> {
+    // Emergency pause, exempt from the timelock
+    pub fn lock(
+        &mut self,
+    ) -> Result<()> {
+        self.pool.check_role(Role::Guardian, self.user.key())?;
+        self.pool.locked = true;
+        Ok(())
+    }
+
+    pub fn unlock(
+        &mut self,
+    ) -> Result<()> {
+        self.apply(AdminAction::Unlock)
+    }
+
+    pub fn set_fee(
+        &mut self,
+        fee: u16
+    ) -> Result<()> {
+        self.apply(AdminAction::SetFee { fee })
+    }
+
+    pub fn set_access_mode(
+        &mut self,
+        access_mode: AccessMode,
+        gate_mint: Option<Pubkey>
+    ) -> Result<()> {
+        self.apply(AdminAction::SetAccessMode { access_mode, gate_mint })
+    }
+
+    pub fn set_roles(
+        &mut self,
+        guardian: Option<Pubkey>,
+        fee_manager: Option<Pubkey>,
+        compliance_authority: Option<Pubkey>
+    ) -> Result<()> {
+        self.apply(AdminAction::SetRoles { guardian, fee_manager, compliance_authority })
+    }
+
+    pub fn set_timelock_delay(
+        &mut self,
+        timelock_delay: i64
+    ) -> Result<()> {
+        self.apply(AdminAction::SetTimelockDelay { timelock_delay })
+    }
+
+    // Changes only take effect straight away while the pool has no timelock
+    pub fn apply(
+        &mut self,
+        action: AdminAction
+    ) -> Result<()> {
+        self.pool.check_role(action.role(), self.user.key())?;
+        require!(self.pool.timelock_delay == 0, AmmError::TimelockActive);
+        action.apply_multi(&mut self.pool)
+    }
+}
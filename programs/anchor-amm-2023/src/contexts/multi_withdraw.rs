@@ -0,0 +1,161 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, transfer, burn, Burn};
+use crate::{assert_non_zero, assert_not_locked, assert_not_expired};
+use crate::state::multi_pool::MultiPool;
+use crate::state::member::Member;
+use crate::errors::AmmError;
+use crate::helpers::access::{check_access, check_owner_not_blocked};
+
+#[derive(Accounts)]
+pub struct MultiWithdraw<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"multi_lp", pool.key().as_ref()],
+        bump = pool.lp_bump
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = mint_lp,
+        constraint = user_lp.owner == user.key() || user_lp.delegate == COption::Some(user.key()) @ AmmError::InvalidTokenOwner
+    )]
+    pub user_lp: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [
+            b"multi",
+            pool.seed.to_le_bytes().as_ref()
+        ],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>,
+    /// CHECK: Blocklist entry of the user, which must not exist
+    #[account(
+        seeds = [b"blocked", pool.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub blocked: UncheckedAccount<'info>,
+    /// Allowlist entry of the user, only checked in gated pools
+    pub member: Option<Box<Account<'info, Member>>>,
+    /// Gate token account of the user, only checked in gated pools
+    pub gate_token: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> MultiWithdraw<'info> {
+    // Remaining accounts: the pool's vaults followed by the user's token account for each asset
+    pub fn withdraw(
+        &self,
+        accounts: &[AccountInfo<'info>],
+        amount: u64, // Amount of LP token to burn
+        min_amounts: Vec<u64>, // Min amount of each asset we are willing to withdraw
+        expiration: i64,
+    ) -> Result<()> {
+        assert_not_locked!(self.pool.locked);
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount]);
+
+        let n = self.pool.mints.len();
+        require!(min_amounts.len() == n && accounts.len() == n * 2, AmmError::InvalidAssetCount);
+
+        let balances = self.pool.load_vaults(accounts)?;
+        let supply = self.mint_lp.supply;
+        require!(amount <= supply, AmmError::InsufficientBalance);
+
+        // Round down in the pool's favour
+        let amounts = balances.iter().map(|b| {
+            let a = (b.amount as u128).checked_mul(amount as u128).ok_or(AmmError::Overflow)?;
+            Ok((a / supply as u128) as u64)
+        }).collect::<Result<Vec<u64>>>()?;
+
+        // Check for slippage
+        require!(amounts.iter().zip(min_amounts.iter()).all(|(a, min)| a >= min), AmmError::SlippageExceeded);
+
+        for (i, a) in amounts.iter().enumerate() {
+            self.withdraw_tokens(accounts[i].clone(), accounts[n + i].clone(), *a)?;
+        }
+        self.burn_lp_tokens(amount)
+    }
+
+    // Remaining accounts: the pool's vaults, the user's token account for the withdrawn asset, then
+    // the blocklist entry of its owner
+    pub fn withdraw_single(
+        &self,
+        accounts: &[AccountInfo<'info>],
+        index: u8,
+        amount: u64, // Amount of LP token to burn
+        min: u64, // Min amount of the asset we are willing to withdraw
+        expiration: i64,
+    ) -> Result<()> {
+        assert_not_locked!(self.pool.locked);
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount]);
+        // Single-sided withdrawals trade against the pool, plain withdrawals are never gated
+        require!(self.blocked.data_is_empty(), AmmError::WalletBlocked);
+        check_access(&self.pool, &self.user.key(), self.member.as_deref(), self.gate_token.as_deref(), true)?;
+
+        let n = self.pool.mints.len();
+        let i = self.pool.check_index(index)?;
+        require!(accounts.len() == n + 2, AmmError::InvalidAssetCount);
+        // The recipient can't be blocked either
+        check_owner_not_blocked(&self.pool.key(), &accounts[n], &accounts[n + 1])?;
+
+        let balances = self.pool.load_vaults(accounts)?;
+        let out = self.pool.single_withdraw_out(i, balances[i].amount, self.mint_lp.supply, amount)?;
+
+        // Check for slippage
+        require!(out >= min, AmmError::SlippageExceeded);
+        assert_non_zero!([out]);
+
+        self.withdraw_tokens(accounts[i].clone(), accounts[n].clone(), out)?;
+        self.burn_lp_tokens(amount)
+    }
+
+    pub fn withdraw_tokens(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        amount: u64
+    ) -> Result<()> {
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.pool.to_account_info(),
+        };
+
+        let seed = self.pool.seed.to_le_bytes();
+        let seeds = &[
+            &b"multi"[..],
+            seed.as_ref(),
+            &[self.pool.pool_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds
+        );
+        transfer(ctx, amount)
+    }
+
+    pub fn burn_lp_tokens(
+        &self,
+        amount: u64
+    ) -> Result<()> {
+        let cpi_accounts = Burn {
+            mint: self.mint_lp.to_account_info(),
+            from: self.user_lp.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+        );
+        burn(ctx, amount)
+    }
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::assert_no_flash_loan;
+use crate::errors::AmmError;
+use crate::state::config::Config;
+use crate::state::observation::{Observation, Observations, Twap};
+
+#[derive(Accounts)]
+pub struct Observe<'info> {
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.bump
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+}
+
+impl<'info> Observe<'info> {
+    // Prices and liquidity averaged over the last `seconds_ago` seconds
+    pub fn observe(
+        &self,
+        seconds_ago: u32
+    ) -> Result<Twap> {
+        assert_no_flash_loan!(self.config.flash_loan_amount);
+        let now = Clock::get()?.unix_timestamp;
+        let start = self.observation_at(now - seconds_ago as i64)?;
+        Twap::between(&start, &self.config.observation(now))
+    }
+
+    // Reserves are constant since the last update, so any point after it can be computed exactly.
+    // Earlier points are interpolated from the stored observations.
+    pub fn observation_at(
+        &self,
+        timestamp: i64
+    ) -> Result<Observation> {
+        match timestamp >= self.config.last_update_ts {
+            true => Ok(self.config.observation(timestamp)),
+            false => self.observations.observe(
+                timestamp,
+                self.config.observation(self.config.last_update_ts)
+            )
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use crate::has_role;
+use crate::state::config::Config;
+use crate::state::admin_action::{AdminAction, AdminQueue};
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct QueueAction<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"admin_queue", config.key().as_ref()],
+        bump,
+        space = AdminQueue::LEN
+    )]
+    pub admin_queue: Box<Account<'info, AdminQueue>>,
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> QueueAction<'info> {
+    pub fn queue_action(
+        &mut self,
+        bumps: &BTreeMap<String, u8>,
+        data: Vec<u8> // Borsh encoded AdminAction
+    ) -> Result<()> {
+        let action = AdminAction::try_from_slice(&data).map_err(|_| AmmError::InvalidAction)?;
+        has_role!(self, action.role());
+
+        if self.admin_queue.config == Pubkey::default() {
+            self.admin_queue.init(
+                self.config.key(),
+                *bumps.get("admin_queue").ok_or(AmmError::BumpError)?
+            );
+        }
+
+        let eta = Clock::get()?.unix_timestamp.checked_add(self.config.timelock_delay).ok_or(AmmError::Overflow)?;
+        let id = self.admin_queue.push(data, eta)?;
+        msg!("Queued admin action {} with ETA {}", id, eta);
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::state::config::Config;
+use crate::state::admin_action::{AdminAction, AdminQueue};
+use crate::state::member::Member;
+
+#[derive(Accounts)]
+pub struct RemoveMember<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        close = user,
+        has_one = config,
+        seeds = [b"member", config.key().as_ref(), member.wallet.as_ref()],
+        bump = member.bump
+    )]
+    pub member: Account<'info, Member>,
+    /// Queue holding the action, needed once the pool has a timelock
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"admin_queue", config.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Option<Box<Account<'info, AdminQueue>>>,
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>
+}
+
+impl<'info> RemoveMember<'info> {
+    pub fn remove_member(
+        &mut self
+    ) -> Result<()> {
+        let wallet = self.member.wallet;
+        AdminAction::RemoveMember { wallet }.authorize(&self.config, self.user.key(), self.admin_queue.as_deref_mut())
+    }
+}
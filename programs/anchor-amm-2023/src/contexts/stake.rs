@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, transfer};
+use anchor_spl::associated_token::{get_associated_token_address, AssociatedToken};
+use crate::assert_non_zero;
+use crate::state::config::Config;
+use crate::state::farm::{Farm, StakeInfo};
+use crate::errors::AmmError;
+use crate::helpers::token::init_user_token_account;
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"stake", farm.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = StakeInfo::LEN
+    )]
+    pub stake_info: Box<Account<'info, StakeInfo>>,
+    #[account(
+        mut,
+        token::mint = mint_lp,
+        constraint = user_lp.owner == user.key() || user_lp.delegate == COption::Some(user.key()) @ AmmError::InvalidTokenOwner
+    )]
+    pub user_lp: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm
+    )]
+    pub farm_lp: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> Stake<'info> {
+    pub fn stake_lp(
+        &mut self,
+        bumps: &BTreeMap<String, u8>,
+        amount: u64
+    ) -> Result<()> {
+        assert_non_zero!([amount]);
+        self.settle(bumps)?;
+
+        self.stake_info.amount = self.stake_info.amount.checked_add(amount).ok_or(AmmError::Overflow)?;
+        self.farm.total_staked = self.farm.total_staked.checked_add(amount).ok_or(AmmError::Overflow)?;
+        self.stake_info.reset_debts(&self.farm.streams)?;
+
+        let accounts = Transfer {
+            from: self.user_lp.to_account_info(),
+            to: self.farm_lp.to_account_info(),
+            authority: self.user.to_account_info()
+        };
+        transfer(CpiContext::new(self.token_program.to_account_info(), accounts), amount)
+    }
+
+    // Rewards earned so far stay pending until claimed
+    pub fn unstake_lp(
+        &mut self,
+        bumps: &BTreeMap<String, u8>,
+        amount: u64
+    ) -> Result<()> {
+        assert_non_zero!([amount]);
+        self.settle(bumps)?;
+
+        self.stake_info.amount = self.stake_info.amount.checked_sub(amount).ok_or(AmmError::InsufficientBalance)?;
+        self.farm.total_staked = self.farm.total_staked.checked_sub(amount).ok_or(AmmError::Underflow)?;
+        self.stake_info.reset_debts(&self.farm.streams)?;
+
+        self.withdraw_from_farm(self.farm_lp.to_account_info(), self.user_lp.to_account_info(), amount)
+    }
+
+    // Remaining accounts: the reward mint, the farm's reward vault and the user's reward token
+    // account for each stream, in the order stored on the farm
+    pub fn claim_rewards(
+        &mut self,
+        bumps: &BTreeMap<String, u8>,
+        accounts: &[AccountInfo<'info>]
+    ) -> Result<()> {
+        self.settle(bumps)?;
+        require!(accounts.len() == self.farm.streams.len() * 3, AmmError::InvalidAssetCount);
+
+        for (i, chunk) in accounts.chunks(3).enumerate() {
+            let (mint, vault, user_reward) = (&chunk[0], &chunk[1], &chunk[2]);
+            require_keys_eq!(mint.key(), self.farm.streams[i].mint, AmmError::InvalidToken);
+            require_keys_eq!(vault.key(), get_associated_token_address(&self.farm.key(), &mint.key()), AmmError::InvalidVault);
+
+            let amount = self.stake_info.pending[i];
+            if amount == 0 {
+                continue;
+            }
+            init_user_token_account(
+                user_reward.clone(),
+                mint.clone(),
+                self.user.to_account_info(),
+                self.token_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+                self.system_program.to_account_info()
+            )?;
+            self.stake_info.pending[i] = 0;
+            self.withdraw_from_farm(vault.clone(), user_reward.clone(), amount)?;
+        }
+        Ok(())
+    }
+
+    // Accrue the farm's emissions and the user's share of them
+    pub fn settle(
+        &mut self,
+        bumps: &BTreeMap<String, u8>
+    ) -> Result<()> {
+        if self.stake_info.owner == Pubkey::default() {
+            self.stake_info.owner = self.user.key();
+            self.stake_info.farm = self.farm.key();
+            self.stake_info.bump = *bumps.get("stake_info").ok_or(AmmError::BumpError)?;
+        }
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        self.stake_info.settle(&self.farm.streams)
+    }
+
+    pub fn withdraw_from_farm(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        amount: u64
+    ) -> Result<()> {
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.farm.to_account_info()
+        };
+
+        let config = self.config.key();
+        let seeds = &[
+            &b"farm"[..],
+            config.as_ref(),
+            &[self.farm.bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, amount)
+    }
+}
@@ -2,9 +2,15 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, transfer};
 use anchor_spl::associated_token::AssociatedToken;
 use constant_product_curve::{ConstantProduct, LiquidityPair};
-use crate::{accounts, assert_not_locked, assert_not_expired, assert_non_zero};
+use crate::{accounts, assert_not_locked, assert_not_expired, assert_non_zero, assert_no_flash_loan};
 use crate::state::config::Config;
+use crate::state::member::Member;
+use crate::state::oracle::OraclePrice;
+use crate::state::observation::Observations;
 use crate::errors::AmmError;
+use crate::helpers::access::{check_access, check_not_blocked, check_owner_not_blocked};
+use crate::helpers::token::{init_user_token_accounts, wrap_sol, unwrap_sol};
+use crate::helpers::pool::update_twap;
 
 #[derive(Accounts)]
 pub struct Swap<'info> {
@@ -12,20 +18,14 @@ pub struct Swap<'info> {
     pub user: Signer<'info>,
     pub mint_x: Box<Account<'info, Mint>>,
     pub mint_y: Box<Account<'info, Mint>>,
-    #[account(
-        init_if_needed,
-        payer = user,
-        associated_token::mint = mint_x,
-        associated_token::authority = user
-    )]
-    pub user_x: Box<Account<'info, TokenAccount>>,
-    #[account(
-        init_if_needed,
-        payer = user,
-        associated_token::mint = mint_y,
-        associated_token::authority = user
-    )]
-    pub user_y: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Any X token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_x: UncheckedAccount<'info>,
+    /// CHECK: Any Y token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_y: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub recipient: Option<Box<Account<'info, TokenAccount>>>,
     #[account(
         mut,
         associated_token::mint = mint_x,
@@ -42,6 +42,7 @@ pub struct Swap<'info> {
     #[account(seeds = [b"auth"], bump = config.auth_bump)]
     pub auth: UncheckedAccount<'info>,
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
         seeds = [
@@ -51,6 +52,30 @@ pub struct Swap<'info> {
         bump = config.config_bump,
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.bump
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+    /// CHECK: Checked against config.oracle and parsed as OraclePrice
+    pub oracle: Option<UncheckedAccount<'info>>,
+    /// CHECK: Blocklist entry of the user, which must not exist
+    #[account(
+        seeds = [b"blocked", config.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub blocked: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of user_x, checked in the handler
+    pub blocked_x: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of user_y, checked in the handler
+    pub blocked_y: UncheckedAccount<'info>,
+    /// CHECK: Blocklist entry of the owner of recipient, checked in the handler
+    pub blocked_recipient: Option<UncheckedAccount<'info>>,
+    /// Allowlist entry of the user, only checked in gated pools
+    pub member: Option<Box<Account<'info, Member>>>,
+    /// Gate token account of the user, only checked in gated pools
+    pub gate_token: Option<Box<Account<'info, TokenAccount>>>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>
@@ -62,33 +87,173 @@ impl<'info> Swap<'info> {
         is_x: bool,
         amount: u64,
         min: u64,
-        expiration: i64
+        expiration: i64,
+        wrap_sol: bool
     ) -> Result<()> {
         assert_not_locked!(self.config.locked);
+        require!(!self.config.swaps_paused, AmmError::SwapsPaused);
+        assert_no_flash_loan!(self.config.flash_loan_amount);
         assert_not_expired!(expiration);
         assert_non_zero!([amount]);
+        require!(self.blocked.data_is_empty(), AmmError::WalletBlocked);
+        check_access(&self.config, &self.user.key(), self.member.as_deref(), self.gate_token.as_deref(), true)?;
+        let created = self.init_user_accounts()?;
+        self.check_blocklist()?;
+
+        // Output can go to any token account of the right mint
+        if let Some(recipient) = &self.recipient {
+            let mint = match is_x {
+                true => self.mint_y.key(),
+                false => self.mint_x.key()
+            };
+            require_keys_eq!(recipient.mint, mint, AmmError::InvalidToken);
+        }
+
+        let (deposit, withdraw) = match self.config.oracle {
+            Some(_) => (amount, self.oracle_swap_amount(is_x, amount, min)?),
+            None => {
+                let mut curve = ConstantProduct::init(
+                    self.vault_x.amount,
+                    self.vault_y.amount,
+                    self.vault_x.amount,
+                    self.config.fee,
+                    None
+                ).map_err(AmmError::from)?;
+
+                let p = match is_x {
+                    true => LiquidityPair::X,
+                    false => LiquidityPair::Y
+                };
+
+                let res = curve.swap(p, amount, min).map_err(AmmError::from)?;
+                (res.deposit, res.withdraw)
+            }
+        };
+
+        assert_non_zero!([deposit, withdraw]);
+        require!(self.check_price_band(is_x, deposit, withdraw)?, AmmError::CircuitBreaker);
+        let (out_x, out_y) = match is_x {
+            true => (0, withdraw),
+            false => (withdraw, 0)
+        };
+        self.config.record_outflow(self.vault_x.amount, self.vault_y.amount, out_x, out_y, Clock::get()?.slot)?;
+        if wrap_sol {
+            match is_x {
+                true => self.wrap_sol(deposit, 0)?,
+                false => self.wrap_sol(0, deposit)?
+            }
+        }
+        self.deposit_token(is_x, deposit)?;
+        self.withdraw_token(is_x, withdraw)?;
+        if wrap_sol {
+            self.unwrap_sol(created)?;
+        }
+        update_twap(&mut self.config, &mut self.observations, &mut self.vault_x, &mut self.vault_y)
+    }
+
+    pub fn check_price_band(
+        &mut self,
+        is_x: bool,
+        deposit: u64,
+        withdraw: u64
+    ) -> Result<bool> {
+        let (x, y) = (self.vault_x.amount, self.vault_y.amount);
+        let (x_after, y_after) = match is_x {
+            true => (x.checked_add(deposit).ok_or(AmmError::Overflow)?, y.checked_sub(withdraw).ok_or(AmmError::Underflow)?),
+            false => (x.checked_sub(withdraw).ok_or(AmmError::Underflow)?, y.checked_add(deposit).ok_or(AmmError::Overflow)?)
+        };
+        Ok(self.config.check_price_band(x, y, x_after, y_after, Clock::get()?.unix_timestamp))
+    }
+
+    pub fn oracle_swap_amount(
+        &self,
+        is_x: bool,
+        amount: u64,
+        min: u64
+    ) -> Result<u64> {
+        let oracle = self.oracle.as_ref().ok_or(AmmError::InvalidOracle)?;
+        require!(Some(oracle.key()) == self.config.oracle, AmmError::InvalidOracle);
+
+        let price = OraclePrice::load(&oracle.to_account_info(), &self.config.oracle_program)?;
+        price.validate(
+            Clock::get()?.unix_timestamp,
+            self.config.max_staleness,
+            self.config.max_confidence
+        )?;
 
-        let mut curve = ConstantProduct::init(
+        let out = price.swap_out(
             self.vault_x.amount,
             self.vault_y.amount,
-            self.vault_x.amount,
+            is_x,
+            amount,
             self.config.fee,
-            None
-        ).map_err(AmmError::from)?;
+            self.config.spread
+        )?;
 
-        let p = match is_x {
-            true => LiquidityPair::X,
-            false => LiquidityPair::Y
-        };
-
-        let res = curve.swap(p, amount, min).map_err(AmmError::from)?;
+        // Check for slippage
+        require!(out >= min, AmmError::SlippageExceeded);
+        Ok(out)
+    }
 
-        assert_non_zero!([res.deposit, res.withdraw]);
-        self.deposit_token(is_x, res.deposit)?;
-        self.withdraw_token(is_x, res.withdraw)?;
+    // Owners of the user's token accounts and the recipient can't be blocked either
+    pub fn check_blocklist(
+        &self
+    ) -> Result<()> {
+        let pool = self.config.key();
+        check_owner_not_blocked(&pool, &self.user_x, &self.blocked_x)?;
+        check_owner_not_blocked(&pool, &self.user_y, &self.blocked_y)?;
+        if let Some(recipient) = &self.recipient {
+            let blocked = self.blocked_recipient.as_ref().ok_or(AmmError::InvalidBlocklistEntry)?;
+            check_not_blocked(&pool, &recipient.owner, blocked)?;
+        }
         Ok(())
     }
 
+    pub fn init_user_accounts(
+        &self
+    ) -> Result<[bool; 2]> {
+        init_user_token_accounts(
+            [
+                (self.user_x.to_account_info(), self.mint_x.to_account_info()),
+                (self.user_y.to_account_info(), self.mint_y.to_account_info())
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info(),
+            self.associated_token_program.to_account_info(),
+            self.system_program.to_account_info()
+        )
+    }
+
+    pub fn wrap_sol(
+        &self,
+        x: u64,
+        y: u64
+    ) -> Result<()> {
+        wrap_sol(
+            [
+                (self.mint_x.key(), self.user_x.to_account_info(), x),
+                (self.mint_y.key(), self.user_y.to_account_info(), y)
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info(),
+            self.system_program.to_account_info()
+        )
+    }
+
+    pub fn unwrap_sol(
+        &self,
+        created: [bool; 2]
+    ) -> Result<()> {
+        unwrap_sol(
+            [
+                (self.mint_x.key(), self.user_x.to_account_info(), created[0]),
+                (self.mint_y.key(), self.user_y.to_account_info(), created[1])
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info()
+        )
+    }
+
     pub fn deposit_token(
         &mut self,
         is_x: bool,
@@ -122,6 +287,10 @@ impl<'info> Swap<'info> {
             true => (self.vault_y.to_account_info(), self.user_y.to_account_info()),
             false => (self.vault_x.to_account_info(), self.user_x.to_account_info())
         };
+        let to = match &self.recipient {
+            Some(recipient) => recipient.to_account_info(),
+            None => to
+        };
 
         let accounts = Transfer {
             from,
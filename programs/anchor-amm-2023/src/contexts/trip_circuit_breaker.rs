@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::assert_no_flash_loan;
+use crate::state::config::Config;
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct TripCircuitBreaker<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> TripCircuitBreaker<'info> {
+    // Trades that would break the band are rejected outright, so anyone can record a trip here
+    // once the reserves have been pushed out of it some other way. Only reserves recorded by the
+    // last TWAP update count, live vault balances can be moved for free by a flash loan or donation.
+    pub fn trip_circuit_breaker(
+        &mut self
+    ) -> Result<()> {
+        assert_no_flash_loan!(self.config.flash_loan_amount);
+        require!(self.config.pause_on_break, AmmError::PauseOnBreakDisabled);
+        require!(
+            self.config.price_band_broken(self.config.reserve_x, self.config.reserve_y, Clock::get()?.unix_timestamp),
+            AmmError::PriceInBand
+        );
+        msg!("Circuit breaker tripped, pausing swaps");
+        self.config.swaps_paused = true;
+        Ok(())
+    }
+}
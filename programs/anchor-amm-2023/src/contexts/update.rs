@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
-use crate::has_update_authority;
+use crate::has_role;
 use crate::state::config::Config;
+use crate::state::role::Role;
+use crate::state::member::AccessMode;
+use crate::state::admin_action::AdminAction;
 use crate::errors::AmmError;
 
 #[derive(Accounts)]
@@ -8,6 +11,7 @@ pub struct Update<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
     #[account(
+        mut,
         seeds = [
             b"config",
             config.seed.to_le_bytes().as_ref()
@@ -19,10 +23,11 @@ pub struct Update<'info> {
 }
 
 impl<'info> Update<'info> {
+    // Emergency pause, exempt from the timelock
     pub fn lock(
         &mut self,
     ) -> Result<()> {
-        has_update_authority!(self);
+        has_role!(self, Role::Guardian);
         self.config.locked = true;
         Ok(())
     }
@@ -30,9 +35,91 @@ impl<'info> Update<'info> {
     pub fn unlock(
         &mut self,
     ) -> Result<()> {
-        has_update_authority!(self);
-        self.config.locked = false;
-        Ok(())
+        self.apply(AdminAction::Unlock)
+    }
+
+    pub fn set_oracle(
+        &mut self,
+        oracle: Option<Pubkey>,
+        oracle_program: Pubkey,
+        max_staleness: i64,
+        max_confidence: u16,
+        spread: u16
+    ) -> Result<()> {
+        self.apply(AdminAction::SetOracle { oracle, oracle_program, max_staleness, max_confidence, spread })
+    }
+
+    pub fn set_fee(
+        &mut self,
+        fee: u16
+    ) -> Result<()> {
+        self.apply(AdminAction::SetFee { fee })
+    }
+
+    pub fn set_flash_fee(
+        &mut self,
+        flash_fee: u16
+    ) -> Result<()> {
+        self.apply(AdminAction::SetFlashFee { flash_fee })
+    }
+
+    pub fn set_circuit_breaker(
+        &mut self,
+        max_price_move: u16,
+        price_window: i64,
+        pause_on_break: bool
+    ) -> Result<()> {
+        self.apply(AdminAction::SetCircuitBreaker { max_price_move, price_window, pause_on_break })
+    }
+
+    pub fn set_outflow_limit(
+        &mut self,
+        max_outflow: u16,
+        outflow_window: u64
+    ) -> Result<()> {
+        self.apply(AdminAction::SetOutflowLimit { max_outflow, outflow_window })
+    }
+
+    pub fn set_deposit_cap(
+        &mut self,
+        max_reserve_x: u64,
+        max_reserve_y: u64
+    ) -> Result<()> {
+        self.apply(AdminAction::SetDepositCap { max_reserve_x, max_reserve_y })
+    }
+
+    pub fn set_access_mode(
+        &mut self,
+        access_mode: AccessMode,
+        gate_mint: Option<Pubkey>
+    ) -> Result<()> {
+        self.apply(AdminAction::SetAccessMode { access_mode, gate_mint })
+    }
+
+    pub fn set_roles(
+        &mut self,
+        guardian: Option<Pubkey>,
+        fee_manager: Option<Pubkey>,
+        compliance_authority: Option<Pubkey>
+    ) -> Result<()> {
+        self.apply(AdminAction::SetRoles { guardian, fee_manager, compliance_authority })
+    }
+
+    pub fn set_timelock_delay(
+        &mut self,
+        timelock_delay: i64
+    ) -> Result<()> {
+        self.apply(AdminAction::SetTimelockDelay { timelock_delay })
+    }
+
+    // Changes only take effect straight away while the pool has no timelock
+    pub fn apply(
+        &mut self,
+        action: AdminAction
+    ) -> Result<()> {
+        has_role!(self, action.role());
+        require!(self.config.timelock_delay == 0, AmmError::TimelockActive);
+        action.apply(&mut self.config)
     }
 }
 
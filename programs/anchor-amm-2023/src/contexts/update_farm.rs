@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::state::config::Config;
+use crate::state::admin_action::{AdminAction, AdminQueue};
+use crate::state::farm::Farm;
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct UpdateFarm<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    /// Queue holding the action, needed once the pool has a timelock
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"admin_queue", config.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Option<Box<Account<'info, AdminQueue>>>,
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>
+}
+
+impl<'info> UpdateFarm<'info> {
+    pub fn set_reward_rate(
+        &mut self,
+        index: u8,
+        emission_rate: u64
+    ) -> Result<()> {
+        AdminAction::SetRewardRate { index, emission_rate }.authorize(&self.config, self.user.key(), self.admin_queue.as_deref_mut())?;
+        require!((index as usize) < self.farm.streams.len(), AmmError::InvalidIndex);
+        // Accrue at the old rate up to now
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        self.farm.streams[index as usize].emission_rate = emission_rate;
+        Ok(())
+    }
+}
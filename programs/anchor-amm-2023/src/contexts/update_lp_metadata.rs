@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use anchor_spl::metadata::{Metadata, UpdateMetadataAccountsV2, update_metadata_accounts_v2};
+use crate::state::config::Config;
+use crate::state::admin_action::{AdminAction, AdminQueue};
+use crate::state::lp_metadata::LpMetadata;
+use crate::helpers::metadata::lp_data;
+
+#[derive(Accounts)]
+pub struct UpdateLpMetadata<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    /// CHECK: Metaplex metadata PDA of the LP mint
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), mint_lp.key().as_ref()],
+        seeds::program = token_metadata_program.key(),
+        bump
+    )]
+    pub lp_metadata: UncheckedAccount<'info>,
+    /// CHECK: just a pda for signing
+    #[account(seeds = [b"auth"], bump = config.auth_bump)]
+    pub auth: UncheckedAccount<'info>,
+    /// Queue holding the action, needed once the pool has a timelock
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"admin_queue", config.key().as_ref()],
+        bump = admin_queue.bump
+    )]
+    pub admin_queue: Option<Box<Account<'info, AdminQueue>>>,
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    pub token_metadata_program: Program<'info, Metadata>
+}
+
+impl<'info> UpdateLpMetadata<'info> {
+    pub fn update_lp_metadata(
+        &mut self,
+        metadata: LpMetadata
+    ) -> Result<()> {
+        AdminAction::UpdateLpMetadata { metadata: metadata.clone() }.authorize(&self.config, self.user.key(), self.admin_queue.as_deref_mut())?;
+
+        let accounts = UpdateMetadataAccountsV2 {
+            metadata: self.lp_metadata.to_account_info(),
+            update_authority: self.auth.to_account_info()
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_metadata_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+        update_metadata_accounts_v2(ctx, None, Some(lp_data(metadata)), None, None)
+    }
+}
@@ -1,11 +1,17 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, transfer, burn, Burn};
 use anchor_spl::associated_token::AssociatedToken;
-use constant_product_curve::ConstantProduct;
+use constant_product_curve::{ConstantProduct, LiquidityPair};
 use solana_program::log;
-use crate::{assert_not_locked, assert_not_expired, assert_non_zero};
+use crate::{assert_not_locked, assert_not_expired, assert_non_zero, assert_no_flash_loan};
 use crate::state::config::Config;
+use crate::state::observation::Observations;
+use crate::state::member::Member;
 use crate::errors::AmmError;
+use crate::helpers::access::{check_access, check_not_blocked, check_owner_not_blocked};
+use crate::helpers::token::{init_user_token_accounts, unwrap_sol};
+use crate::helpers::pool::update_twap;
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
@@ -31,31 +37,34 @@ pub struct Withdraw<'info> {
         associated_token::authority = auth,
     )]
     pub vault_y: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Any X token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_x: UncheckedAccount<'info>,
+    /// CHECK: Any Y token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_y: UncheckedAccount<'info>,
     #[account(
-        init_if_needed,
-        payer = user,
-        associated_token::mint = mint_x,
-        associated_token::authority = user,
+        mut,
+        token::mint = mint_lp,
+        constraint = user_lp.owner == user.key() || user_lp.delegate == COption::Some(user.key()) @ AmmError::InvalidTokenOwner
     )]
-    pub user_x: Box<Account<'info, TokenAccount>>,
+    pub user_lp: Box<Account<'info, TokenAccount>>,
     #[account(
-        init_if_needed,
-        payer = user,
-        associated_token::mint = mint_y,
-        associated_token::authority = user,
+        mut,
+        token::mint = mint_x
     )]
-    pub user_y: Box<Account<'info, TokenAccount>>,
+    pub recipient_x: Option<Box<Account<'info, TokenAccount>>>,
     #[account(
         mut,
-        associated_token::mint = mint_lp,
-        associated_token::authority = user,
+        token::mint = mint_y
     )]
-    pub user_lp: Box<Account<'info, TokenAccount>>,
+    pub recipient_y: Option<Box<Account<'info, TokenAccount>>>,
     
     /// CHECK: just a pda for signing
     #[account(seeds = [b"auth"], bump = config.auth_bump)]
     pub auth: UncheckedAccount<'info>,
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
         seeds = [
@@ -65,6 +74,20 @@ pub struct Withdraw<'info> {
         bump = config.config_bump,
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.bump
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+    /// CHECK: Blocklist entry of the user, only checked for single-sided withdrawals
+    pub blocked: Option<UncheckedAccount<'info>>,
+    /// CHECK: Blocklist entry of the owner of the account paid by a single-sided withdrawal
+    pub blocked_receiver: Option<UncheckedAccount<'info>>,
+    /// Allowlist entry of the user, only checked in gated pools
+    pub member: Option<Box<Account<'info, Member>>>,
+    /// Gate token account of the user, only checked in gated pools
+    pub gate_token: Option<Box<Account<'info, TokenAccount>>>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -72,15 +95,18 @@ pub struct Withdraw<'info> {
 
 impl<'info> Withdraw<'info> {
     pub fn withdraw(
-        &self,
+        &mut self,
         amount: u64, // Amount of LP token to burn
         min_x: u64, // Min amount of X we are willing to withdraw
         min_y: u64, // Min amount of Y we are willing to withdraw
         expiration: i64,
+        wrap_sol: bool, // Unwrap a native mint side to lamports if its wSOL account is created here
     ) -> Result<()> {
         assert_not_locked!(self.config.locked);
+        assert_no_flash_loan!(self.config.flash_loan_amount);
         assert_not_expired!(expiration);
         assert_non_zero!([amount]);
+        let created = self.init_user_accounts()?;
 
         let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
             self.vault_x.amount,
@@ -92,10 +118,139 @@ impl<'info> Withdraw<'info> {
 
         // Check for slippage
         require!(min_x <= amounts.x && min_y <= amounts.y, AmmError::SlippageExceeded);
+        self.config.record_outflow(self.vault_x.amount, self.vault_y.amount, amounts.x, amounts.y, Clock::get()?.slot)?;
         
         self.withdraw_tokens(true, amounts.x)?;
         self.withdraw_tokens(false, amounts.y)?;
-        self.burn_lp_tokens(amount)
+        if wrap_sol {
+            self.unwrap_sol(created)?;
+        }
+        self.burn_lp_tokens(amount)?;
+        update_twap(&mut self.config, &mut self.observations, &mut self.vault_x, &mut self.vault_y)
+    }
+
+    pub fn withdraw_single(
+        &mut self,
+        is_x: bool,
+        amount: u64, // Amount of LP token to burn
+        min: u64, // Min amount of X or Y we are willing to withdraw
+        expiration: i64,
+        wrap_sol: bool, // Unwrap a native mint side to lamports if its wSOL account is created here
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        // The withdrawal trades against the pool, so it's paused and banded like a swap
+        require!(!self.config.swaps_paused, AmmError::SwapsPaused);
+        assert_no_flash_loan!(self.config.flash_loan_amount);
+        assert_not_expired!(expiration);
+        assert_non_zero!([amount]);
+        // Single-sided withdrawals trade against the pool, plain withdrawals are never gated
+        check_access(&self.config, &self.user.key(), self.member.as_deref(), self.gate_token.as_deref(), true)?;
+        let created = self.init_user_accounts()?;
+        self.check_blocklist(is_x)?;
+        require!(self.config.oracle.is_none(), AmmError::OraclePool);
+
+        let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            amount,
+            6
+        ).map_err(AmmError::from)?;
+
+        // Swap the unwanted side back into the pool at its post-withdrawal reserves
+        let mut curve = ConstantProduct::init(
+            self.vault_x.amount - amounts.x,
+            self.vault_y.amount - amounts.y,
+            self.vault_x.amount - amounts.x,
+            self.config.fee,
+            None
+        ).map_err(AmmError::from)?;
+
+        let (p, swap_amount, withdrawn) = match is_x {
+            true => (LiquidityPair::Y, amounts.y, amounts.x),
+            false => (LiquidityPair::X, amounts.x, amounts.y)
+        };
+
+        let swapped = match swap_amount {
+            0 => 0,
+            _ => curve.swap(p, swap_amount, 0).map_err(AmmError::from)?.withdraw
+        };
+        let out = withdrawn.checked_add(swapped).ok_or(AmmError::Overflow)?;
+
+        // Check for slippage
+        require!(min <= out, AmmError::SlippageExceeded);
+        assert_non_zero!([out]);
+        let (out_x, out_y) = match is_x {
+            true => (out, 0),
+            false => (0, out)
+        };
+        require!(
+            self.config.check_price_band(
+                self.vault_x.amount,
+                self.vault_y.amount,
+                self.vault_x.amount - out_x,
+                self.vault_y.amount - out_y,
+                Clock::get()?.unix_timestamp
+            ),
+            AmmError::CircuitBreaker
+        );
+        // The swapped side never leaves the pool, so the net outflow is the withdrawn share plus the swap output
+        self.config.record_outflow(self.vault_x.amount, self.vault_y.amount, out_x, out_y, Clock::get()?.slot)?;
+
+        self.withdraw_tokens(is_x, out)?;
+        if wrap_sol {
+            self.unwrap_sol(created)?;
+        }
+        self.burn_lp_tokens(amount)?;
+        update_twap(&mut self.config, &mut self.observations, &mut self.vault_x, &mut self.vault_y)
+    }
+
+    // Single-sided withdrawals are blocked like swaps, for the user and whoever they pay out to.
+    // Plain withdrawals skip this so blocked wallets can still exit.
+    pub fn check_blocklist(
+        &self,
+        is_x: bool
+    ) -> Result<()> {
+        let pool = self.config.key();
+        let blocked = self.blocked.as_ref().ok_or(AmmError::InvalidBlocklistEntry)?;
+        check_not_blocked(&pool, &self.user.key(), blocked)?;
+        let receiver = match (is_x, &self.recipient_x, &self.recipient_y) {
+            (true, Some(recipient), _) => recipient.to_account_info(),
+            (true, None, _) => self.user_x.to_account_info(),
+            (false, _, Some(recipient)) => recipient.to_account_info(),
+            (false, _, None) => self.user_y.to_account_info()
+        };
+        let blocked_receiver = self.blocked_receiver.as_ref().ok_or(AmmError::InvalidBlocklistEntry)?;
+        check_owner_not_blocked(&pool, &receiver, blocked_receiver)
+    }
+
+    pub fn init_user_accounts(
+        &self
+    ) -> Result<[bool; 2]> {
+        init_user_token_accounts(
+            [
+                (self.user_x.to_account_info(), self.mint_x.to_account_info()),
+                (self.user_y.to_account_info(), self.mint_y.to_account_info())
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info(),
+            self.associated_token_program.to_account_info(),
+            self.system_program.to_account_info()
+        )
+    }
+
+    pub fn unwrap_sol(
+        &self,
+        created: [bool; 2]
+    ) -> Result<()> {
+        unwrap_sol(
+            [
+                (self.mint_x.key(), self.user_x.to_account_info(), created[0]),
+                (self.mint_y.key(), self.user_y.to_account_info(), created[1])
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info()
+        )
     }
 
     pub fn withdraw_tokens(
@@ -103,9 +258,11 @@ impl<'info> Withdraw<'info> {
         is_x: bool,
         amount:u64
     ) -> Result<()> {  
-        let (from, to) = match is_x {
-            true => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
-            false => (self.vault_y.to_account_info(), self.user_y.to_account_info())
+        let (from, to) = match (is_x, &self.recipient_x, &self.recipient_y) {
+            (true, Some(recipient), _) => (self.vault_x.to_account_info(), recipient.to_account_info()),
+            (true, None, _) => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
+            (false, _, Some(recipient)) => (self.vault_y.to_account_info(), recipient.to_account_info()),
+            (false, _, None) => (self.vault_y.to_account_info(), self.user_y.to_account_info())
         };
 
         let cpi_accounts = Transfer {
@@ -0,0 +1,229 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, Burn, CloseAccount, transfer, burn, close_account};
+use anchor_spl::associated_token::AssociatedToken;
+use constant_product_curve::ConstantProduct;
+use crate::{assert_not_locked, assert_not_expired, assert_no_flash_loan};
+use crate::state::config::Config;
+use crate::state::observation::Observations;
+use crate::state::lp_lock::LpLock;
+use crate::state::position::Position;
+use crate::events::PositionClosed;
+use crate::errors::AmmError;
+use crate::helpers::token::init_user_token_accounts;
+use crate::helpers::pool::update_twap;
+
+#[derive(Accounts)]
+pub struct WithdrawPosition<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = auth,
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = auth,
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Any X token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_x: UncheckedAccount<'info>,
+    /// CHECK: Any Y token account of the user, created if it's their missing ATA
+    #[account(mut)]
+    pub user_y: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = user,
+        constraint = user_nft.amount == 1 @ AmmError::InsufficientBalance
+    )]
+    pub user_nft: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        close = user,
+        has_one = config,
+        has_one = nft_mint,
+        seeds = [b"position", nft_mint.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = position
+    )]
+    pub position_lp: Box<Account<'info, TokenAccount>>,
+    /// CHECK: just a pda for signing
+    #[account(seeds = [b"auth"], bump = config.auth_bump)]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.bump
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> WithdrawPosition<'info> {
+    // Burns the position NFT and withdraws all of its liquidity
+    pub fn withdraw_position(
+        &mut self,
+        min_x: u64, // Min amount of X we are willing to withdraw
+        min_y: u64, // Min amount of Y we are willing to withdraw
+        expiration: i64,
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        assert_no_flash_loan!(self.config.flash_loan_amount);
+        assert_not_expired!(expiration);
+        let now = Clock::get()?.unix_timestamp;
+        self.init_user_accounts()?;
+
+        let (x, y, amount) = (self.vault_x.amount, self.vault_y.amount, self.position.lp_amount);
+        let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
+            x,
+            y,
+            self.mint_lp.supply,
+            amount,
+            6
+        ).map_err(AmmError::from)?;
+
+        // Check for slippage
+        require!(min_x <= amounts.x && min_y <= amounts.y, AmmError::SlippageExceeded);
+        self.config.record_outflow(x, y, amounts.x, amounts.y, Clock::get()?.slot)?;
+
+        let liquidity_per_lp = LpLock::liquidity_per_lp(x, y, self.mint_lp.supply)?;
+        let (fees_x, fees_y) = self.position.fees(liquidity_per_lp, amounts.x, amounts.y)?;
+        emit!(PositionClosed {
+            position: self.position.key(),
+            nft_mint: self.nft_mint.key(),
+            owner: self.user.key(),
+            withdrawn_x: amounts.x,
+            withdrawn_y: amounts.y,
+            fees_x,
+            fees_y,
+            impermanent_loss: self.position.impermanent_loss(x, y, amounts.x - fees_x, amounts.y - fees_y),
+            duration: now - self.position.entry_ts
+        });
+
+        self.withdraw_tokens(true, amounts.x)?;
+        self.withdraw_tokens(false, amounts.y)?;
+        self.burn_nft()?;
+        self.burn_position_lp()?;
+        update_twap(&mut self.config, &mut self.observations, &mut self.vault_x, &mut self.vault_y)
+    }
+
+    pub fn init_user_accounts(
+        &self
+    ) -> Result<()> {
+        init_user_token_accounts(
+            [
+                (self.user_x.to_account_info(), self.mint_x.to_account_info()),
+                (self.user_y.to_account_info(), self.mint_y.to_account_info())
+            ],
+            self.user.to_account_info(),
+            self.token_program.to_account_info(),
+            self.associated_token_program.to_account_info(),
+            self.system_program.to_account_info()
+        )?;
+        Ok(())
+    }
+
+    pub fn withdraw_tokens(
+        &self,
+        is_x: bool,
+        amount: u64
+    ) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.user_y.to_account_info())
+        };
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.auth.to_account_info()
+        };
+
+        let seeds = &[
+            &b"auth"[..],
+            &[self.config.auth_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, amount)
+    }
+
+    pub fn burn_nft(
+        &self
+    ) -> Result<()> {
+        let accounts = Burn {
+            mint: self.nft_mint.to_account_info(),
+            from: self.user_nft.to_account_info(),
+            authority: self.user.to_account_info()
+        };
+        burn(CpiContext::new(self.token_program.to_account_info(), accounts), 1)
+    }
+
+    // Burn the position's LP tokens and close its now empty token account. The whole balance is
+    // burnt, so LP anyone donated to the account goes to the pool instead of blocking the close.
+    pub fn burn_position_lp(
+        &self
+    ) -> Result<()> {
+        let nft_mint = self.nft_mint.key();
+        let seeds = &[
+            &b"position"[..],
+            nft_mint.as_ref(),
+            &[self.position.bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let accounts = Burn {
+            mint: self.mint_lp.to_account_info(),
+            from: self.position_lp.to_account_info(),
+            authority: self.position.to_account_info()
+        };
+        burn(CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds), self.position_lp.amount)?;
+
+        let accounts = CloseAccount {
+            account: self.position_lp.to_account_info(),
+            destination: self.user.to_account_info(),
+            authority: self.position.to_account_info()
+        };
+        close_account(CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds))
+    }
+}
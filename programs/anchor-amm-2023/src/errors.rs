@@ -40,6 +40,72 @@ pub enum AmmError {
     InsufficientBalance,
     #[msg("Zero balance.")]
     ZeroBalance,
+    #[msg("Invalid oracle account.")]
+    InvalidOracle,
+    #[msg("Oracle price is stale.")]
+    StaleOracle,
+    #[msg("Oracle confidence interval is too wide.")]
+    OracleConfidence,
+    #[msg("Invalid number of assets.")]
+    InvalidAssetCount,
+    #[msg("Invalid asset weights.")]
+    InvalidWeights,
+    #[msg("Invalid vault account.")]
+    InvalidVault,
+    #[msg("Invalid asset index.")]
+    InvalidIndex,
+    #[msg("Not supported in oracle mode.")]
+    OraclePool,
+    #[msg("A flash loan is in progress.")]
+    FlashLoanActive,
+    #[msg("No flash loan in progress.")]
+    NoFlashLoan,
+    #[msg("Flash loan is not repaid in this transaction.")]
+    FlashLoanNotRepaid,
+    #[msg("Flash loans can't be taken through CPI.")]
+    FlashLoanCpi,
+    #[msg("Constant product invariant violated.")]
+    InvariantViolated,
+    #[msg("Token account is not owned by or delegated to the signer.")]
+    InvalidTokenOwner,
+    #[msg("Observation is older than the oldest one stored.")]
+    ObservationTooOld,
+    #[msg("Trade moves the price beyond the circuit breaker band.")]
+    CircuitBreaker,
+    #[msg("Swaps are paused by the circuit breaker.")]
+    SwapsPaused,
+    #[msg("Reserves are within the circuit breaker band.")]
+    PriceInBand,
+    #[msg("Circuit breaker is not set to pause swaps.")]
+    PauseOnBreakDisabled,
+    #[msg("Outflow exceeds the rate limit for this window.")]
+    OutflowLimitExceeded,
+    #[msg("Farm already has the maximum number of reward streams.")]
+    TooManyRewardStreams,
+    #[msg("LP tokens are still locked.")]
+    StillLocked,
+    #[msg("Unlock time can't be earlier than the current one.")]
+    InvalidUnlockTime,
+    #[msg("LP metadata needs the metadata account, metadata program and rent sysvar.")]
+    MissingMetadataAccounts,
+    #[msg("Deposit would exceed the pool's reserve cap.")]
+    DepositCapExceeded,
+    #[msg("Wallet isn't on the pool's allowlist and holds no gate token.")]
+    NotAMember,
+    #[msg("Wallet is blocked from this pool.")]
+    WalletBlocked,
+    #[msg("Invalid blocklist entry account.")]
+    InvalidBlocklistEntry,
+    #[msg("Pool has a timelock, queue this action instead.")]
+    TimelockActive,
+    #[msg("Action isn't ready to execute yet.")]
+    ActionNotReady,
+    #[msg("No queued action with this id.")]
+    ActionNotFound,
+    #[msg("Invalid admin action.")]
+    InvalidAction,
+    #[msg("Admin action queue is full.")]
+    ActionQueueFull,
 }
 
 impl From<CurveError> for AmmError {
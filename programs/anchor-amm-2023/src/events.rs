@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct PositionClosed {
+    pub position: Pubkey,
+    pub nft_mint: Pubkey,
+    pub owner: Pubkey,
+    pub withdrawn_x: u64,
+    pub withdrawn_y: u64,
+    pub fees_x: u64,             // Part of the withdrawal earned as swap fees
+    pub fees_y: u64,
+    pub impermanent_loss: i64,   // Value lost versus holding the deposit, in Y at the exit price
+    pub duration: i64            // Seconds the position was open
+}
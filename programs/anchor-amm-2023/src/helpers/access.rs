@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::state::member::{AccessMode, Member};
+use crate::errors::AmmError;
+
+// Pools that can restrict who provides liquidity and trades
+pub trait Gated {
+    fn access_mode(&self) -> AccessMode;
+    fn gate_mint(&self) -> Option<Pubkey>;
+}
+
+// In a gated pool the user proves membership with their allowlist PDA or a gate token balance
+pub fn check_access<'info, T: Gated + AccountSerialize + AccountDeserialize + Owner + Clone>(
+    pool: &Account<'info, T>,
+    user: &Pubkey,
+    member: Option<&Account<Member>>,
+    gate_token: Option<&Account<TokenAccount>>,
+    is_trade: bool
+) -> Result<()> {
+    if !pool.access_mode().is_gated(is_trade) {
+        return Ok(());
+    }
+    if let Some(member) = member {
+        if member.config == pool.key() && member.wallet == *user {
+            return Ok(());
+        }
+    }
+    if let (Some(gate_token), Some(gate_mint)) = (gate_token, pool.gate_mint()) {
+        if gate_token.mint == gate_mint && gate_token.owner == *user && gate_token.amount > 0 {
+            return Ok(());
+        }
+    }
+    err!(AmmError::NotAMember)
+}
+
+// The blocklist entry passed in for a wallet has to be its PDA, and must not exist
+pub fn check_not_blocked(
+    pool: &Pubkey,
+    wallet: &Pubkey,
+    blocked: &AccountInfo
+) -> Result<()> {
+    let (address, _) = Pubkey::find_program_address(&[b"blocked", pool.as_ref(), wallet.as_ref()], &crate::ID);
+    require_keys_eq!(blocked.key(), address, AmmError::InvalidBlocklistEntry);
+    require!(blocked.data_is_empty(), AmmError::WalletBlocked);
+    Ok(())
+}
+
+// Signers can act for other wallets as delegates or pay out to their accounts, so the owners
+// of the token accounts a trade moves tokens through are checked too
+pub fn check_owner_not_blocked(
+    pool: &Pubkey,
+    token_account: &AccountInfo,
+    blocked: &AccountInfo
+) -> Result<()> {
+    let owner = Account::<TokenAccount>::try_from(token_account)?.owner;
+    check_not_blocked(pool, &owner, blocked)
+}
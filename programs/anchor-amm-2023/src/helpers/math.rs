@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use crate::constants::MINIMUM_LIQUIDITY;
+use crate::errors::AmmError;
+
+// 18 decimal fixed point helpers for weighted pool math
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+const LN_2: u128 = 693_147_180_559_945_309;
+// Worst case relative error of pow(), padded when rounding in the pool's favour
+const MAX_POW_ERROR: u128 = 10_000;
+
+pub fn mul_down(a: u128, b: u128) -> Result<u128> {
+    Ok(a.checked_mul(b).ok_or(AmmError::Overflow)? / WAD)
+}
+
+pub fn mul_up(a: u128, b: u128) -> Result<u128> {
+    let p = a.checked_mul(b).ok_or(AmmError::Overflow)?;
+    Ok(p / WAD + u128::from(p % WAD != 0))
+}
+
+pub fn div_down(a: u128, b: u128) -> Result<u128> {
+    require!(b > 0, AmmError::ZeroBalance);
+    Ok(a.checked_mul(WAD).ok_or(AmmError::Overflow)? / b)
+}
+
+pub fn div_up(a: u128, b: u128) -> Result<u128> {
+    require!(b > 0, AmmError::ZeroBalance);
+    let p = a.checked_mul(WAD).ok_or(AmmError::Overflow)?;
+    Ok(p / b + u128::from(p % b != 0))
+}
+
+// Natural log of a positive fixed point number
+pub fn ln(x: u128) -> Result<i128> {
+    require!(x > 0, AmmError::InvalidAmount);
+
+    // Normalise to m in [1, 2) so that ln(x) = k * ln(2) + ln(m)
+    let (mut m, mut k) = (x, 0i128);
+    while m >= 2 * WAD {
+        m /= 2;
+        k += 1;
+    }
+    while m < WAD {
+        m *= 2;
+        k -= 1;
+    }
+
+    // ln(m) = 2 * atanh(z) = 2 * (z + z^3/3 + z^5/5 + ...), z = (m - 1) / (m + 1)
+    let z = (m - WAD) * WAD / (m + WAD);
+    let z2 = z * z / WAD;
+    let (mut term, mut sum, mut n) = (z, 0u128, 1u128);
+    while term > 0 {
+        sum += term / n;
+        term = term * z2 / WAD;
+        n += 2;
+    }
+
+    Ok(k * LN_2 as i128 + 2 * sum as i128)
+}
+
+// e^x for a signed fixed point exponent
+pub fn exp(x: i128) -> Result<u128> {
+    // exp(x) = 2^k * exp(r), 0 <= r < ln(2)
+    let k = x.div_euclid(LN_2 as i128);
+    let r = x.rem_euclid(LN_2 as i128) as u128;
+
+    let (mut term, mut sum, mut n) = (WAD, WAD, 1u128);
+    while term > 0 {
+        term = term * r / WAD / n;
+        sum += term;
+        n += 1;
+    }
+
+    match k >= 0 {
+        true => {
+            require!(k < 64, AmmError::Overflow);
+            Ok(sum.checked_mul(1u128 << k).ok_or(AmmError::Overflow)?)
+        },
+        false => Ok(sum.checked_shr((-k) as u32).unwrap_or(0))
+    }
+}
+
+// base^e for fixed point base and exponent
+pub fn pow(base: u128, e: u128) -> Result<u128> {
+    if e == 0 {
+        return Ok(WAD);
+    }
+    if base == 0 {
+        return Ok(0);
+    }
+    let l = ln(base)?;
+    let e = i128::try_from(e).map_err(|_| AmmError::Overflow)?;
+    // l * e / WAD, split to keep the intermediate product in range
+    let w = WAD as i128;
+    let product = (l / w).checked_mul(e).ok_or(AmmError::Overflow)?
+        .checked_add((l % w).checked_mul(e).ok_or(AmmError::Overflow)? / w).ok_or(AmmError::Overflow)?;
+    exp(product)
+}
+
+pub fn pow_up(base: u128, e: u128) -> Result<u128> {
+    let p = pow(base, e)?;
+    Ok(p + mul_up(p, MAX_POW_ERROR)? + 1)
+}
+
+pub fn pow_down(base: u128, e: u128) -> Result<u128> {
+    let p = pow(base, e)?;
+    Ok(p.saturating_sub(mul_up(p, MAX_POW_ERROR)? + 1))
+}
+
+// prod(x_i^w_i) for fixed point values and weights, rounded down
+pub fn weighted_product_down(terms: &[(u128, u128)]) -> Result<u128> {
+    let w = WAD as i128;
+    let mut sum = 0i128;
+    for (x, e) in terms {
+        let e = i128::try_from(*e).map_err(|_| AmmError::Overflow)?;
+        sum = sum.checked_add(ln(*x)?.checked_mul(e).ok_or(AmmError::Overflow)? / w).ok_or(AmmError::Overflow)?;
+    }
+    let p = exp(sum)?;
+    // Pad by dividing as p can be too large to multiply by the error
+    Ok(p.saturating_sub(p / (WAD / MAX_POW_ERROR) + 1))
+}
+
+// Integer square root, rounded down
+pub fn sqrt(x: u128) -> u128 {
+    if x < 2 {
+        return x;
+    }
+    let mut a = 1u128 << ((128 - x.leading_zeros()) / 2 + 1);
+    let mut b = (a + x / a) / 2;
+    while b < a {
+        a = b;
+        b = (a + x / a) / 2;
+    }
+    a
+}
+
+// LP minted by a pair pool's first deposit, the geometric mean of the amounts. MINIMUM_LIQUIDITY
+// of it is locked forever so the supply can never be emptied and re-seeded at a skewed price.
+pub fn initial_liquidity(x: u64, y: u64) -> Result<u64> {
+    let liquidity = u64::try_from(sqrt(x as u128 * y as u128)).map_err(|_| AmmError::Overflow)?;
+    require!(liquidity > MINIMUM_LIQUIDITY, AmmError::LiquidityLessThanMinimum);
+    Ok(liquidity)
+}
+
+// Amount of a single sided deposit to swap so that the remainder matches the pool ratio:
+// s = (sqrt(R * (R * (2 - f)^2 + 4 * (1 - f) * a)) - R * (2 - f)) / (2 * (1 - f)), R = reserve
+pub fn zap_swap_amount(reserve: u64, amount: u64, fee: u16) -> Result<u64> {
+    require!(reserve > 0, AmmError::NoLiquidityInPool);
+    require!(fee < 10000, AmmError::InvalidFee);
+    const S: u128 = 1_000_000_000_000;
+    let (f, r) = (10000u128, 10000u128 - fee as u128);
+    // Scale a / reserve to avoid overflowing the product under the root
+    let q = (amount as u128) * S / reserve as u128;
+    let z = ((f + r) * (f + r)).checked_mul(S).ok_or(AmmError::Overflow)?
+        .checked_add((4 * r * f).checked_mul(q).ok_or(AmmError::Overflow)?).ok_or(AmmError::Overflow)?;
+    let root = sqrt(z.checked_mul(S).ok_or(AmmError::Overflow)?);
+    let s = (reserve as u128)
+        .checked_mul(root.saturating_sub((f + r) * S)).ok_or(AmmError::Overflow)?
+        / (2 * r * S);
+    Ok(s.min(amount as u128) as u64)
+}
+
+// Full 256 bit product of two u128s as (high, low) words, for comparing large products
+pub fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    let (a0, a1) = (a as u64 as u128, a >> 64);
+    let (b0, b1) = (b as u64 as u128, b >> 64);
+    let (p00, p01, p10, p11) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+    let mid = (p00 >> 64) + (p01 as u64 as u128) + (p10 as u64 as u128);
+    let lo = (p00 as u64 as u128) | (mid << 64);
+    let hi = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (hi, lo)
+}
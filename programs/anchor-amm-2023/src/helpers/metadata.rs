@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::{create_metadata_accounts_v3, CreateMetadataAccountsV3};
+use mpl_token_metadata::state::DataV2;
+use crate::state::lp_metadata::LpMetadata;
+use crate::errors::AmmError;
+
+// Accounts needed to create an LP mint's metadata. The Metaplex ones are optional on the
+// instructions that create pools, so metadata is only created when they're passed.
+pub struct LpMetadataAccounts<'info> {
+    pub metadata: Option<AccountInfo<'info>>,
+    pub token_metadata_program: Option<AccountInfo<'info>>,
+    pub rent: Option<AccountInfo<'info>>,
+    pub mint: AccountInfo<'info>,
+    pub auth: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>
+}
+
+// LP mints are always created with the classic token program, so their metadata lives in a
+// Metaplex account rather than the Token-2022 metadata extension.
+pub fn create_lp_metadata<'info>(
+    accounts: LpMetadataAccounts<'info>,
+    auth_bump: u8,
+    args: LpMetadata
+) -> Result<()> {
+    let (metadata, token_metadata_program, rent) = match (accounts.metadata, accounts.token_metadata_program, accounts.rent) {
+        (Some(m), Some(p), Some(r)) => (m, p, r),
+        _ => return err!(AmmError::MissingMetadataAccounts)
+    };
+
+    let accounts = CreateMetadataAccountsV3 {
+        metadata,
+        mint: accounts.mint,
+        mint_authority: accounts.auth.clone(),
+        payer: accounts.payer,
+        update_authority: accounts.auth,
+        system_program: accounts.system_program,
+        rent
+    };
+
+    let seeds = &[
+        &b"auth"[..],
+        &[auth_bump],
+    ];
+
+    let signer_seeds = &[&seeds[..]];
+
+    let ctx = CpiContext::new_with_signer(
+        token_metadata_program,
+        accounts,
+        signer_seeds
+    );
+    create_metadata_accounts_v3(ctx, lp_data(args), true, true, None)
+}
+
+pub fn lp_data(
+    args: LpMetadata
+) -> DataV2 {
+    DataV2 {
+        name: args.name,
+        symbol: args.symbol,
+        uri: args.uri,
+        seller_fee_basis_points: 0,
+        creators: None,
+        collection: None,
+        uses: None
+    }
+}
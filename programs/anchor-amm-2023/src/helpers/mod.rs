@@ -1,3 +1,9 @@
+pub mod math;
+pub mod token;
+pub mod metadata;
+pub mod access;
+pub mod pool;
+
 #[macro_export]
 macro_rules! assert_non_zero {
     ($array:expr) => {
@@ -16,6 +22,15 @@ macro_rules! assert_not_locked {
     };
 }
 
+#[macro_export]
+macro_rules! assert_no_flash_loan {
+    ($amount:expr) => {
+        if $amount != 0 {
+            return err!(AmmError::FlashLoanActive)
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! assert_not_expired {
     ($expiration:expr) => {
@@ -26,14 +41,9 @@ macro_rules! assert_not_expired {
 }
 
 #[macro_export]
-macro_rules! has_update_authority {
-    ($x:expr) => {
-        match $x.config.authority {
-            Some(a) => {
-                require_keys_eq!(a, $x.user.key(), AmmError::InvalidAuthority);
-            },
-            None => return err!(AmmError::NoAuthoritySet)
-        }
+macro_rules! has_role {
+    ($x:expr, $role:expr) => {
+        $x.config.check_role($role, $x.user.key())?
     };
 }
 
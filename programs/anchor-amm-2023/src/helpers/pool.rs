@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::errors::AmmError;
+use crate::state::config::Config;
+use crate::state::observation::Observations;
+use crate::state::lp_metadata::LpMetadata;
+use crate::helpers::metadata::{create_lp_metadata, LpMetadataAccounts};
+
+// Setup shared by every instruction that creates a pair pool
+pub struct PoolInit<'a, 'info> {
+    pub config: &'a mut Account<'info, Config>,
+    pub observations: &'a mut Account<'info, Observations>,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub accounts: LpMetadataAccounts<'info>
+}
+
+impl<'a, 'info> PoolInit<'a, 'info> {
+    pub fn init(
+        self,
+        bumps: &BTreeMap<String, u8>,
+        seed: u64,
+        fee: u16,
+        authority: Option<Pubkey>,
+        metadata: Option<LpMetadata>
+    ) -> Result<()> {
+        // Don't charge >100.00% as a fee
+        require!(fee <= 10000, AmmError::InvalidFee);
+
+        let (auth_bump, config_bump, lp_bump) = (
+            *bumps.get("auth").ok_or(AmmError::BumpError)?,
+            *bumps.get("config").ok_or(AmmError::BumpError)?,
+            *bumps.get("mint_lp").ok_or(AmmError::BumpError)?
+        );
+        self.config.init(
+            seed,
+            authority,
+            self.mint_x,
+            self.mint_y,
+            fee,
+            auth_bump,
+            config_bump,
+            lp_bump
+        );
+        self.observations.init(
+            self.config.key(),
+            *bumps.get("observations").ok_or(AmmError::BumpError)?
+        );
+        // Metadata is created whenever its accounts are passed, named after the pair by default
+        if metadata.is_some() || self.accounts.metadata.is_some() {
+            let metadata = metadata.unwrap_or_else(|| LpMetadata::for_pair(&self.mint_x, &self.mint_y));
+            create_lp_metadata(self.accounts, auth_bump, metadata)?;
+        }
+        Ok(())
+    }
+}
+
+// Accumulate the TWAP at the reserves from the last update, then record the new ones
+pub fn update_twap<'info>(
+    config: &mut Account<'info, Config>,
+    observations: &mut Account<'info, Observations>,
+    vault_x: &mut Account<'info, TokenAccount>,
+    vault_y: &mut Account<'info, TokenAccount>
+) -> Result<()> {
+    vault_x.reload()?;
+    vault_y.reload()?;
+    let observation = config.update_twap(vault_x.amount, vault_y.amount, Clock::get()?.unix_timestamp);
+    observations.write(observation);
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{create, get_associated_token_address, Create};
+use anchor_spl::token::{TokenAccount, CloseAccount, SyncNative, close_account, sync_native};
+use anchor_spl::token::spl_token::native_mint;
+use crate::errors::AmmError;
+
+// User side token accounts can be any account of the right mint that the user owns or is
+// a delegate of. If the user's ATA is passed in and doesn't exist yet, it's created first.
+pub fn init_user_token_account<'info>(
+    account: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    user: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    associated_token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>
+) -> Result<()> {
+    if account.data_is_empty() && account.key() == get_associated_token_address(&user.key(), &mint.key()) {
+        let accounts = Create {
+            payer: user.clone(),
+            associated_token: account.clone(),
+            authority: user.clone(),
+            mint: mint.clone(),
+            system_program,
+            token_program,
+        };
+        create(CpiContext::new(associated_token_program, accounts))?;
+    }
+
+    let token = Account::<TokenAccount>::try_from(&account)?;
+    require_keys_eq!(token.mint, mint.key(), AmmError::InvalidToken);
+    require!(
+        token.owner == user.key() || token.delegate == COption::Some(user.key()),
+        AmmError::InvalidTokenOwner
+    );
+    Ok(())
+}
+
+// Create the user's ATAs if needed and check they can spend from their token accounts, given as
+// (account, mint) pairs. Returns which of them were created by this instruction.
+pub fn init_user_token_accounts<'info, const N: usize>(
+    accounts: [(AccountInfo<'info>, AccountInfo<'info>); N],
+    user: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    associated_token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>
+) -> Result<[bool; N]> {
+    let mut created = [false; N];
+    for (i, (account, mint)) in accounts.into_iter().enumerate() {
+        created[i] = account.data_is_empty();
+        init_user_token_account(
+            account,
+            mint,
+            user.clone(),
+            token_program.clone(),
+            associated_token_program.clone(),
+            system_program.clone()
+        )?;
+    }
+    Ok(created)
+}
+
+// Wrap lamports into the user's wSOL account for whichever of the (mint, account, amount)
+// sides is the native mint
+pub fn wrap_sol<'info>(
+    sides: [(Pubkey, AccountInfo<'info>, u64); 2],
+    user: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>
+) -> Result<()> {
+    for (mint, account, amount) in sides {
+        wrap_native(
+            &mint,
+            account,
+            user.clone(),
+            token_program.clone(),
+            system_program.clone(),
+            amount
+        )?;
+    }
+    Ok(())
+}
+
+// Close the wSOL accounts of the (mint, account, created) sides that this instruction created,
+// so the user is left holding plain lamports. Accounts that already existed may hold other wSOL
+// or belong to someone else, so they stay open.
+pub fn unwrap_sol<'info>(
+    sides: [(Pubkey, AccountInfo<'info>, bool); 2],
+    user: AccountInfo<'info>,
+    token_program: AccountInfo<'info>
+) -> Result<()> {
+    for (mint, account, created) in sides {
+        if !created {
+            continue;
+        }
+        unwrap_native(
+            &mint,
+            account,
+            user.clone(),
+            token_program.clone()
+        )?;
+    }
+    Ok(())
+}
+
+// Wrap lamports from the user into their wSOL account, if the mint is the native mint
+pub fn wrap_native<'info>(
+    mint: &Pubkey,
+    account: AccountInfo<'info>,
+    user: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    amount: u64
+) -> Result<()> {
+    if *mint != native_mint::ID {
+        return Ok(());
+    }
+    let accounts = system_program::Transfer {
+        from: user,
+        to: account.clone(),
+    };
+    system_program::transfer(CpiContext::new(system_program, accounts), amount)?;
+    sync_native(CpiContext::new(token_program, SyncNative { account }))
+}
+
+// Close a wSOL account owned by the user, returning its balance to them as lamports
+pub fn unwrap_native<'info>(
+    mint: &Pubkey,
+    account: AccountInfo<'info>,
+    user: AccountInfo<'info>,
+    token_program: AccountInfo<'info>
+) -> Result<()> {
+    if *mint != native_mint::ID {
+        return Ok(());
+    }
+    let accounts = CloseAccount {
+        account,
+        destination: user.clone(),
+        authority: user,
+    };
+    close_account(CpiContext::new(token_program, accounts))
+}
@@ -5,8 +5,13 @@ use contexts::*;
 mod constants;
 mod state;
 mod errors;
+mod events;
 mod helpers;
 
+use state::observation::Twap;
+use state::lp_metadata::LpMetadata;
+use state::member::AccessMode;
+
 declare_id!("GiVFHELhmrVa7sMGZUcG52M3rfqzPXNsZ9AokuKD8Tmy");
 
 #[program]
@@ -17,10 +22,24 @@ pub mod anchor_amm_2023 {
         ctx: Context<Initialize>, 
         seed: u64, 
         fee: u16, // Fee as basis points
-        authority: Option<Pubkey> // Update authority (if required)
+        authority: Option<Pubkey>, // Update authority (if required)
+        metadata: Option<LpMetadata>, // Name, symbol and uri of the LP token (defaults to the pair)
     ) -> Result<()> {
         // Initialise our AMM config
-        ctx.accounts.init(&ctx.bumps, seed, fee, authority)
+        ctx.accounts.init(&ctx.bumps, seed, fee, authority, metadata)
+    }
+
+    pub fn initialize_with_liquidity(
+        ctx: Context<InitializeWithLiquidity>,
+        seed: u64,
+        fee: u16, // Fee as basis points
+        authority: Option<Pubkey>, // Update authority (if required)
+        amount_x: u64, // Amount of X to seed the pool with
+        amount_y: u64, // Amount of Y to seed the pool with
+        metadata: Option<LpMetadata>, // Name, symbol and uri of the LP token (defaults to the pair)
+    ) -> Result<()> {
+        // Initialise our AMM config and deposit its first liquidity atomically
+        ctx.accounts.init(&ctx.bumps, seed, fee, authority, amount_x, amount_y, metadata)
     }
 
     pub fn deposit(
@@ -29,9 +48,46 @@ pub mod anchor_amm_2023 {
         max_x: u64, // Max amount of X we are willing to deposit
         max_y: u64, // Max amount of Y we are willing to deposit
         expiration: i64,
+        wrap_sol: bool, // Pay a native mint side in lamports, closing wSOL ATAs created here
     ) -> Result<()> {
         // Deposit liquidity to swap
-        ctx.accounts.deposit(amount, max_x, max_y, expiration)
+        ctx.accounts.deposit(amount, max_x, max_y, expiration, wrap_sol)
+    }
+
+    pub fn deposit_exact_x(
+        ctx: Context<Deposit>,
+        amount_x: u64, // Amount of X we want to deposit
+        max_y: u64, // Max amount of Y we are willing to deposit
+        min_lp: u64, // Min amount of LP token we are willing to claim
+        expiration: i64,
+        wrap_sol: bool, // Pay a native mint side in lamports, closing wSOL ATAs created here
+    ) -> Result<()> {
+        // Deposit liquidity to swap by exact amount of X
+        ctx.accounts.deposit_exact(true, amount_x, max_y, min_lp, expiration, wrap_sol)
+    }
+
+    pub fn deposit_exact_y(
+        ctx: Context<Deposit>,
+        amount_y: u64, // Amount of Y we want to deposit
+        max_x: u64, // Max amount of X we are willing to deposit
+        min_lp: u64, // Min amount of LP token we are willing to claim
+        expiration: i64,
+        wrap_sol: bool, // Pay a native mint side in lamports, closing wSOL ATAs created here
+    ) -> Result<()> {
+        // Deposit liquidity to swap by exact amount of Y
+        ctx.accounts.deposit_exact(false, amount_y, max_x, min_lp, expiration, wrap_sol)
+    }
+
+    pub fn deposit_single(
+        ctx: Context<Deposit>,
+        is_x: bool, // Deposit X (or Y), swapping part of it for the other side
+        amount_in: u64, // Amount of tokens we deposit
+        min_lp: u64, // Min amount of LP token we are willing to claim
+        expiration: i64,
+        wrap_sol: bool, // Pay a native mint side in lamports, closing wSOL ATAs created here
+    ) -> Result<()> {
+        // Deposit liquidity to swap from a single token
+        ctx.accounts.deposit_single(is_x, amount_in, min_lp, expiration, wrap_sol)
     }
 
     pub fn withdraw(
@@ -40,9 +96,44 @@ pub mod anchor_amm_2023 {
         min_x: u64, // Minimum amount of liquidity we are willing to receive
         min_y: u64, // Minimum amount of liquidity we are willing to receive
         expiration: i64,
+        wrap_sol: bool, // Receive a native mint side in lamports, closing wSOL ATAs created here
     ) -> Result<()> {
         // Withdraw liquidity from swap
-        ctx.accounts.withdraw(amount, min_x, min_y, expiration)
+        ctx.accounts.withdraw(amount, min_x, min_y, expiration, wrap_sol)
+    }
+
+    pub fn withdraw_single(
+        ctx: Context<Withdraw>,
+        is_x: bool, // Withdraw X (or Y), swapping the other side back into the pool
+        amount: u64, // Amount of liquidity tokens to burn
+        min: u64, // Minimum amount of tokens we are willing to receive
+        expiration: i64,
+        wrap_sol: bool, // Receive a native mint side in lamports, closing wSOL ATAs created here
+    ) -> Result<()> {
+        // Withdraw liquidity from swap into a single token
+        ctx.accounts.withdraw_single(is_x, amount, min, expiration, wrap_sol)
+    }
+
+    pub fn deposit_position(
+        ctx: Context<DepositPosition>,
+        amount: u64, // Amount of LP token to claim
+        max_x: u64, // Max amount of X we are willing to deposit
+        max_y: u64, // Max amount of Y we are willing to deposit
+        expiration: i64,
+        wrap_sol: bool, // Pay a native mint side in lamports, closing wSOL ATAs created here
+    ) -> Result<()> {
+        // Deposit liquidity into a position represented by a new NFT
+        ctx.accounts.deposit_position(&ctx.bumps, amount, max_x, max_y, expiration, wrap_sol)
+    }
+
+    pub fn withdraw_position(
+        ctx: Context<WithdrawPosition>,
+        min_x: u64, // Minimum amount of X we are willing to receive
+        min_y: u64, // Minimum amount of Y we are willing to receive
+        expiration: i64,
+    ) -> Result<()> {
+        // Burn a position NFT and withdraw all of its liquidity
+        ctx.accounts.withdraw_position(min_x, min_y, expiration)
     }
 
     pub fn swap(
@@ -50,10 +141,53 @@ pub mod anchor_amm_2023 {
         is_x: bool,
         amount: u64, // Amount of tokens we deposit
         min: u64, // Minimum amount of tokens I'd be willing to withdraw
-        expiration: i64
+        expiration: i64,
+        wrap_sol: bool // Pay and receive a native mint side in lamports, closing wSOL ATAs created here
     ) -> Result<()> {
         // Swap Token X for Token Y or vice versa
-        ctx.accounts.swap(is_x, amount, min, expiration)
+        ctx.accounts.swap(is_x, amount, min, expiration, wrap_sol)
+    }
+
+    pub fn flash_loan(
+        ctx: Context<Flash>,
+        is_x: bool, // Borrow X (or Y)
+        amount: u64, // Amount of tokens to borrow
+    ) -> Result<()> {
+        // Borrow from the vaults, must be followed by flash_repay in the same transaction
+        ctx.accounts.flash_loan(is_x, amount)
+    }
+
+    pub fn flash_repay(
+        ctx: Context<Flash>,
+    ) -> Result<()> {
+        // Repay the open flash loan plus fee
+        ctx.accounts.flash_repay()
+    }
+
+    pub fn flash_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, FlashSwap<'info>>,
+        receive_x: bool, // Receive X (or Y) up front
+        amount_out: u64, // Amount of tokens we receive
+        data: Vec<u8>, // Instruction data for the callback program
+    ) -> Result<()> {
+        // Receive tokens, pay for them in a callback, then check the curve invariant
+        ctx.accounts.flash_swap(ctx.remaining_accounts, receive_x, amount_out, data)
+    }
+
+    pub fn observe(
+        ctx: Context<Observe>,
+        seconds_ago: u32, // Length of the window ending now to average over
+    ) -> Result<Twap> {
+        // Return the time-weighted average prices over the window, callable through CPI
+        ctx.accounts.observe(seconds_ago)
+    }
+
+    pub fn increase_observation_cardinality(
+        ctx: Context<IncreaseObservationCardinality>,
+        cardinality: u16, // Number of observations to keep
+    ) -> Result<()> {
+        // Grow the pool's observation buffer, paid for by the caller
+        ctx.accounts.increase_observation_cardinality(cardinality)
     }
 
     pub fn lock(
@@ -67,4 +201,374 @@ pub mod anchor_amm_2023 {
     ) -> Result<()> {
         ctx.accounts.unlock()
     }
+
+    pub fn set_oracle(
+        ctx: Context<Update>,
+        oracle: Option<Pubkey>, // Reference price account (None to disable oracle mode)
+        oracle_program: Pubkey, // Program that owns the price account
+        max_staleness: i64, // Max age of oracle price in seconds
+        max_confidence: u16, // Max confidence interval in basis points
+        spread: u16, // Spread around oracle price in basis points
+    ) -> Result<()> {
+        ctx.accounts.set_oracle(oracle, oracle_program, max_staleness, max_confidence, spread)
+    }
+
+    pub fn set_fee(
+        ctx: Context<Update>,
+        fee: u16, // Swap fee in basis points
+    ) -> Result<()> {
+        ctx.accounts.set_fee(fee)
+    }
+
+    pub fn set_flash_fee(
+        ctx: Context<Update>,
+        flash_fee: u16, // Flash loan fee in basis points
+    ) -> Result<()> {
+        ctx.accounts.set_flash_fee(flash_fee)
+    }
+
+    pub fn trip_circuit_breaker(
+        ctx: Context<TripCircuitBreaker>
+    ) -> Result<()> {
+        // Pause swaps if the reserves have left the circuit breaker band
+        ctx.accounts.trip_circuit_breaker()
+    }
+
+    pub fn set_circuit_breaker(
+        ctx: Context<Update>,
+        max_price_move: u16, // Max spot price move per window in basis points (0 to disable)
+        price_window: i64, // Window length in seconds
+        pause_on_break: bool, // Let anyone pause swaps until unlock once reserves break the band
+    ) -> Result<()> {
+        ctx.accounts.set_circuit_breaker(max_price_move, price_window, pause_on_break)
+    }
+
+    pub fn set_outflow_limit(
+        ctx: Context<Update>,
+        max_outflow: u16, // Max outflow per window in basis points of reserves (0 to disable)
+        outflow_window: u64, // Window length in slots
+    ) -> Result<()> {
+        ctx.accounts.set_outflow_limit(max_outflow, outflow_window)
+    }
+
+    pub fn set_deposit_cap(
+        ctx: Context<Update>,
+        max_reserve_x: u64, // Max X reserves after a deposit (0 for no cap)
+        max_reserve_y: u64, // Max Y reserves after a deposit (0 for no cap)
+    ) -> Result<()> {
+        ctx.accounts.set_deposit_cap(max_reserve_x, max_reserve_y)
+    }
+
+    pub fn set_access_mode(
+        ctx: Context<Update>,
+        access_mode: AccessMode, // Who can deposit and trade
+        gate_mint: Option<Pubkey>, // Token proving membership (None for allowlist only)
+    ) -> Result<()> {
+        ctx.accounts.set_access_mode(access_mode, gate_mint)
+    }
+
+    pub fn add_member(
+        ctx: Context<AddMember>,
+        wallet: Pubkey, // Wallet to allow in a gated pool
+    ) -> Result<()> {
+        ctx.accounts.add_member(&ctx.bumps, wallet)
+    }
+
+    pub fn remove_member(
+        ctx: Context<RemoveMember>,
+    ) -> Result<()> {
+        ctx.accounts.remove_member()
+    }
+
+    pub fn set_roles(
+        ctx: Context<Update>,
+        guardian: Option<Pubkey>, // Key allowed to pause the pool
+        fee_manager: Option<Pubkey>, // Key allowed to change fees
+        compliance_authority: Option<Pubkey>, // Key allowed to block wallets
+    ) -> Result<()> {
+        ctx.accounts.set_roles(guardian, fee_manager, compliance_authority)
+    }
+
+    pub fn set_timelock_delay(
+        ctx: Context<Update>,
+        timelock_delay: i64, // Seconds admin actions wait in the queue (0 to apply directly)
+    ) -> Result<()> {
+        ctx.accounts.set_timelock_delay(timelock_delay)
+    }
+
+    pub fn queue_action(
+        ctx: Context<QueueAction>,
+        data: Vec<u8>, // Borsh encoded AdminAction
+    ) -> Result<()> {
+        // Queue an admin action to be executed once the timelock delay has passed
+        ctx.accounts.queue_action(&ctx.bumps, data)
+    }
+
+    pub fn execute_action(
+        ctx: Context<ExecuteAction>,
+        id: u64, // Id of the queued action
+    ) -> Result<()> {
+        ctx.accounts.execute_action(id)
+    }
+
+    pub fn cancel_action(
+        ctx: Context<CancelAction>,
+        id: u64, // Id of the queued action
+    ) -> Result<()> {
+        ctx.accounts.cancel_action(id)
+    }
+
+    pub fn block_wallet(
+        ctx: Context<BlockWallet>,
+        wallet: Pubkey, // Wallet to block from trading and depositing
+    ) -> Result<()> {
+        ctx.accounts.block_wallet(&ctx.bumps, wallet)
+    }
+
+    pub fn unblock_wallet(
+        ctx: Context<UnblockWallet>,
+    ) -> Result<()> {
+        ctx.accounts.unblock_wallet()
+    }
+
+    pub fn update_lp_metadata(
+        ctx: Context<UpdateLpMetadata>,
+        metadata: LpMetadata, // New name, symbol and uri of the LP token
+    ) -> Result<()> {
+        ctx.accounts.update_lp_metadata(metadata)
+    }
+
+    pub fn initialize_farm(
+        ctx: Context<InitializeFarm>,
+        emission_rate: u64, // Reward tokens emitted per second
+        start_ts: i64, // Unix timestamp emissions start at
+        end_ts: i64, // Unix timestamp emissions stop at
+    ) -> Result<()> {
+        // Create a farm paying rewards to LP stakers of a pool
+        ctx.accounts.init(&ctx.bumps, emission_rate, start_ts, end_ts)
+    }
+
+    pub fn stake_lp(
+        ctx: Context<Stake>,
+        amount: u64, // Amount of LP token to stake
+    ) -> Result<()> {
+        // Stake LP tokens to earn farm rewards
+        ctx.accounts.stake_lp(&ctx.bumps, amount)
+    }
+
+    pub fn unstake_lp(
+        ctx: Context<Stake>,
+        amount: u64, // Amount of LP token to unstake
+    ) -> Result<()> {
+        // Unstake LP tokens, keeping earned rewards pending
+        ctx.accounts.unstake_lp(&ctx.bumps, amount)
+    }
+
+    pub fn claim_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, Stake<'info>>,
+    ) -> Result<()> {
+        // Claim earned rewards from every stream of the farm
+        ctx.accounts.claim_rewards(&ctx.bumps, ctx.remaining_accounts)
+    }
+
+    pub fn add_reward_stream(
+        ctx: Context<AddRewardStream>,
+        emission_rate: u64, // Reward tokens emitted per second
+        start_ts: i64, // Unix timestamp emissions start at
+        end_ts: i64, // Unix timestamp emissions stop at
+    ) -> Result<()> {
+        // Add another reward token to a farm
+        ctx.accounts.add_reward_stream(emission_rate, start_ts, end_ts)
+    }
+
+    pub fn fund_reward_stream(
+        ctx: Context<FundRewardStream>,
+        amount: u64, // Amount of reward tokens to add
+    ) -> Result<()> {
+        // Top up the rewards a stream can emit
+        ctx.accounts.fund_reward_stream(amount)
+    }
+
+    pub fn set_reward_rate(
+        ctx: Context<UpdateFarm>,
+        index: u8, // Index of the reward stream
+        emission_rate: u64, // Reward tokens emitted per second
+    ) -> Result<()> {
+        ctx.accounts.set_reward_rate(index, emission_rate)
+    }
+
+    pub fn lock_lp(
+        ctx: Context<LockLp>,
+        amount: u64, // Amount of LP token to lock
+        unlock_ts: i64, // Unix timestamp the LP token can be unlocked after
+    ) -> Result<()> {
+        // Escrow LP tokens until unlock_ts
+        ctx.accounts.lock_lp(&ctx.bumps, amount, unlock_ts)
+    }
+
+    pub fn unlock_lp(
+        ctx: Context<LockLp>,
+    ) -> Result<()> {
+        // Return all locked LP tokens once unlock_ts has passed
+        ctx.accounts.unlock_lp()
+    }
+
+    pub fn claim_lock_fees(
+        ctx: Context<LockLp>,
+        min_x: u64, // Min amount of X we are willing to receive
+        min_y: u64, // Min amount of Y we are willing to receive
+    ) -> Result<()> {
+        // Withdraw swap fees earned by locked LP tokens without unlocking them
+        ctx.accounts.claim_lock_fees(min_x, min_y)
+    }
+
+    pub fn multi_initialize<'info>(
+        ctx: Context<'_, '_, '_, 'info, MultiInitialize<'info>>,
+        seed: u64,
+        fee: u16, // Fee as basis points
+        authority: Option<Pubkey>, // Update authority (if required)
+        weights: Vec<u16>, // Asset weights as basis points, one per vault in remaining accounts
+    ) -> Result<()> {
+        // Initialise a multi-asset pool
+        ctx.accounts.init(&ctx.bumps, ctx.remaining_accounts, seed, fee, authority, weights)
+    }
+
+    pub fn multi_deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, MultiDeposit<'info>>,
+        amount: u64, // Amount of LP token to claim, or the minimum to claim on the first deposit
+        max_amounts: Vec<u64>, // Max amount of each asset we are willing to deposit
+        expiration: i64,
+    ) -> Result<()> {
+        ctx.accounts.deposit(ctx.remaining_accounts, amount, max_amounts, expiration)
+    }
+
+    pub fn multi_deposit_single<'info>(
+        ctx: Context<'_, '_, '_, 'info, MultiDeposit<'info>>,
+        index: u8, // Index of the asset to deposit
+        amount: u64, // Amount of the asset to deposit
+        min_lp: u64, // Min amount of LP token we are willing to claim
+        expiration: i64,
+    ) -> Result<()> {
+        ctx.accounts.deposit_single(ctx.remaining_accounts, index, amount, min_lp, expiration)
+    }
+
+    pub fn multi_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, MultiWithdraw<'info>>,
+        amount: u64, // Amount of LP token to burn
+        min_amounts: Vec<u64>, // Min amount of each asset we are willing to receive
+        expiration: i64,
+    ) -> Result<()> {
+        ctx.accounts.withdraw(ctx.remaining_accounts, amount, min_amounts, expiration)
+    }
+
+    pub fn multi_withdraw_single<'info>(
+        ctx: Context<'_, '_, '_, 'info, MultiWithdraw<'info>>,
+        index: u8, // Index of the asset to receive
+        amount: u64, // Amount of LP token to burn
+        min: u64, // Min amount of the asset we are willing to receive
+        expiration: i64,
+    ) -> Result<()> {
+        ctx.accounts.withdraw_single(ctx.remaining_accounts, index, amount, min, expiration)
+    }
+
+    pub fn multi_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, MultiSwap<'info>>,
+        in_index: u8, // Index of the asset we deposit
+        out_index: u8, // Index of the asset we withdraw
+        amount: u64, // Amount of tokens we deposit
+        min: u64, // Minimum amount of tokens I'd be willing to withdraw
+        expiration: i64
+    ) -> Result<()> {
+        ctx.accounts.swap(ctx.remaining_accounts, in_index, out_index, amount, min, expiration)
+    }
+
+    pub fn multi_lock(
+        ctx: Context<MultiUpdate>,
+    ) -> Result<()> {
+        ctx.accounts.lock()
+    }
+
+    pub fn multi_unlock(
+        ctx: Context<MultiUpdate>,
+    ) -> Result<()> {
+        ctx.accounts.unlock()
+    }
+
+    pub fn multi_set_fee(
+        ctx: Context<MultiUpdate>,
+        fee: u16, // Swap fee in basis points
+    ) -> Result<()> {
+        ctx.accounts.set_fee(fee)
+    }
+
+    pub fn multi_set_access_mode(
+        ctx: Context<MultiUpdate>,
+        access_mode: AccessMode, // Who can deposit and trade
+        gate_mint: Option<Pubkey>, // Token proving membership (None for allowlist only)
+    ) -> Result<()> {
+        ctx.accounts.set_access_mode(access_mode, gate_mint)
+    }
+
+    pub fn multi_set_roles(
+        ctx: Context<MultiUpdate>,
+        guardian: Option<Pubkey>, // Key allowed to pause the pool
+        fee_manager: Option<Pubkey>, // Key allowed to change fees
+        compliance_authority: Option<Pubkey>, // Key allowed to block wallets
+    ) -> Result<()> {
+        ctx.accounts.set_roles(guardian, fee_manager, compliance_authority)
+    }
+
+    pub fn multi_set_timelock_delay(
+        ctx: Context<MultiUpdate>,
+        timelock_delay: i64, // Seconds admin actions wait in the queue (0 to apply directly)
+    ) -> Result<()> {
+        ctx.accounts.set_timelock_delay(timelock_delay)
+    }
+
+    pub fn multi_queue_action(
+        ctx: Context<MultiQueueAction>,
+        data: Vec<u8>, // Borsh encoded AdminAction
+    ) -> Result<()> {
+        ctx.accounts.queue_action(&ctx.bumps, data)
+    }
+
+    pub fn multi_execute_action(
+        ctx: Context<MultiExecuteAction>,
+        id: u64, // Id of the queued action
+    ) -> Result<()> {
+        ctx.accounts.execute_action(id)
+    }
+
+    pub fn multi_cancel_action(
+        ctx: Context<MultiCancelAction>,
+        id: u64, // Id of the queued action
+    ) -> Result<()> {
+        ctx.accounts.cancel_action(id)
+    }
+
+    pub fn multi_add_member(
+        ctx: Context<MultiAddMember>,
+        wallet: Pubkey, // Wallet to allow in a gated multi-asset pool
+    ) -> Result<()> {
+        ctx.accounts.add_member(&ctx.bumps, wallet)
+    }
+
+    pub fn multi_remove_member(
+        ctx: Context<MultiRemoveMember>,
+    ) -> Result<()> {
+        ctx.accounts.remove_member()
+    }
+
+    pub fn multi_block_wallet(
+        ctx: Context<MultiBlockWallet>,
+        wallet: Pubkey, // Wallet to block from trading and depositing
+    ) -> Result<()> {
+        ctx.accounts.block_wallet(&ctx.bumps, wallet)
+    }
+
+    pub fn multi_unblock_wallet(
+        ctx: Context<MultiUnblockWallet>,
+    ) -> Result<()> {
+        ctx.accounts.unblock_wallet()
+    }
 }
\ No newline at end of file
@@ -0,0 +1,256 @@
+use crate::constants::*;
+use crate::errors::AmmError;
+use crate::state::config::Config;
+use crate::state::multi_pool::MultiPool;
+use crate::state::member::AccessMode;
+use crate::state::lp_metadata::LpMetadata;
+use crate::state::role::Role;
+use anchor_lang::prelude::*;
+
+// A change to a pool's parameters, applied straight away or queued behind the timelock
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum AdminAction {
+    Unlock,
+    SetOracle { oracle: Option<Pubkey>, oracle_program: Pubkey, max_staleness: i64, max_confidence: u16, spread: u16 },
+    SetFee { fee: u16 },
+    SetFlashFee { flash_fee: u16 },
+    SetCircuitBreaker { max_price_move: u16, price_window: i64, pause_on_break: bool },
+    SetOutflowLimit { max_outflow: u16, outflow_window: u64 },
+    SetDepositCap { max_reserve_x: u64, max_reserve_y: u64 },
+    SetAccessMode { access_mode: AccessMode, gate_mint: Option<Pubkey> },
+    SetRoles { guardian: Option<Pubkey>, fee_manager: Option<Pubkey>, compliance_authority: Option<Pubkey> },
+    SetTimelockDelay { timelock_delay: i64 },
+    AddMember { wallet: Pubkey },
+    RemoveMember { wallet: Pubkey },
+    BlockWallet { wallet: Pubkey },
+    UnblockWallet { wallet: Pubkey },
+    UpdateLpMetadata { metadata: LpMetadata },
+    InitializeFarm { reward_mint: Pubkey, emission_rate: u64, start_ts: i64, end_ts: i64 },
+    AddRewardStream { reward_mint: Pubkey, emission_rate: u64, start_ts: i64, end_ts: i64 },
+    SetRewardRate { index: u8, emission_rate: u64 }
+}
+
+impl AdminAction {
+    pub fn role(
+        &self
+    ) -> Role {
+        match self {
+            AdminAction::SetFee { .. } | AdminAction::SetFlashFee { .. } => Role::FeeManager,
+            AdminAction::BlockWallet { .. } | AdminAction::UnblockWallet { .. } => Role::Compliance,
+            _ => Role::Admin
+        }
+    }
+
+    pub fn apply(
+        self,
+        config: &mut Config
+    ) -> Result<()> {
+        match self {
+            AdminAction::Unlock => {
+                config.locked = false;
+                config.swaps_paused = false;
+            },
+            AdminAction::SetOracle { oracle, oracle_program, max_staleness, max_confidence, spread } => {
+                require!(max_staleness >= 0 && max_confidence <= 10000, AmmError::InvalidAmount);
+                // Don't quote a spread >100.00%
+                require!(spread <= 10000, AmmError::InvalidFee);
+                config.oracle = oracle;
+                config.oracle_program = oracle_program;
+                config.max_staleness = max_staleness;
+                config.max_confidence = max_confidence;
+                config.spread = spread;
+            },
+            AdminAction::SetFee { fee } => {
+                // Don't charge >100.00% as a fee
+                require!(fee <= 10000, AmmError::InvalidFee);
+                config.fee = fee;
+            },
+            AdminAction::SetFlashFee { flash_fee } => {
+                // Don't charge >100.00% as a fee
+                require!(flash_fee <= 10000, AmmError::InvalidFee);
+                config.flash_fee = flash_fee;
+            },
+            AdminAction::SetCircuitBreaker { max_price_move, price_window, pause_on_break } => {
+                require!(max_price_move <= 10000 && price_window >= 0, AmmError::InvalidAmount);
+                config.max_price_move = max_price_move;
+                config.price_window = price_window;
+                config.pause_on_break = pause_on_break;
+                // Start a fresh window on the next swap
+                config.window_price = 0;
+            },
+            AdminAction::SetOutflowLimit { max_outflow, outflow_window } => {
+                require!(max_outflow <= 10000, AmmError::InvalidAmount);
+                require!(max_outflow == 0 || outflow_window > 0, AmmError::InvalidAmount);
+                config.max_outflow = max_outflow;
+                config.outflow_window = outflow_window;
+                // Start a fresh window on the next outflow
+                config.outflow_window_start = 0;
+            },
+            AdminAction::SetDepositCap { max_reserve_x, max_reserve_y } => {
+                config.max_reserve_x = max_reserve_x;
+                config.max_reserve_y = max_reserve_y;
+            },
+            AdminAction::SetAccessMode { access_mode, gate_mint } => {
+                config.access_mode = access_mode;
+                config.gate_mint = gate_mint;
+            },
+            AdminAction::SetRoles { guardian, fee_manager, compliance_authority } => {
+                config.guardian = guardian;
+                config.fee_manager = fee_manager;
+                config.compliance_authority = compliance_authority;
+            },
+            AdminAction::SetTimelockDelay { timelock_delay } => {
+                require!(timelock_delay >= 0, AmmError::InvalidAmount);
+                config.timelock_delay = timelock_delay;
+            },
+            // These need accounts of their own, so their instructions execute them through authorize
+            AdminAction::AddMember { .. }
+            | AdminAction::RemoveMember { .. }
+            | AdminAction::BlockWallet { .. }
+            | AdminAction::UnblockWallet { .. }
+            | AdminAction::UpdateLpMetadata { .. }
+            | AdminAction::InitializeFarm { .. }
+            | AdminAction::AddRewardStream { .. }
+            | AdminAction::SetRewardRate { .. } => return err!(AmmError::InvalidAction)
+        }
+        Ok(())
+    }
+
+    // Multi-asset pools only support a subset of the actions
+    pub fn apply_multi(
+        self,
+        pool: &mut MultiPool
+    ) -> Result<()> {
+        match self {
+            AdminAction::Unlock => pool.locked = false,
+            AdminAction::SetFee { fee } => {
+                // Don't charge >100.00% as a fee
+                require!(fee <= 10000, AmmError::InvalidFee);
+                pool.fee = fee;
+            },
+            AdminAction::SetAccessMode { access_mode, gate_mint } => {
+                pool.access_mode = access_mode;
+                pool.gate_mint = gate_mint;
+            },
+            AdminAction::SetRoles { guardian, fee_manager, compliance_authority } => {
+                pool.guardian = guardian;
+                pool.fee_manager = fee_manager;
+                pool.compliance_authority = compliance_authority;
+            },
+            AdminAction::SetTimelockDelay { timelock_delay } => {
+                require!(timelock_delay >= 0, AmmError::InvalidAmount);
+                pool.timelock_delay = timelock_delay;
+            },
+            _ => return err!(AmmError::InvalidAction)
+        }
+        Ok(())
+    }
+
+    // For actions run from their own instruction. Without a queue they only take effect straight
+    // away while the pool has no timelock, with one they consume the matching queued action.
+    pub fn authorize(
+        &self,
+        config: &Account<Config>,
+        user: Pubkey,
+        admin_queue: Option<&mut Account<AdminQueue>>
+    ) -> Result<()> {
+        config.check_role(self.role(), user)?;
+        self.consume(config.timelock_delay, admin_queue)
+    }
+
+    pub fn authorize_multi(
+        &self,
+        pool: &Account<MultiPool>,
+        user: Pubkey,
+        admin_queue: Option<&mut Account<AdminQueue>>
+    ) -> Result<()> {
+        pool.check_role(self.role(), user)?;
+        self.consume(pool.timelock_delay, admin_queue)
+    }
+
+    fn consume(
+        &self,
+        timelock_delay: i64,
+        admin_queue: Option<&mut Account<AdminQueue>>
+    ) -> Result<()> {
+        match admin_queue {
+            Some(admin_queue) => {
+                let data = self.try_to_vec().map_err(|_| AmmError::InvalidAction)?;
+                admin_queue.take(&data, Clock::get()?.unix_timestamp)
+            },
+            None => {
+                require!(timelock_delay == 0, AmmError::TimelockActive);
+                Ok(())
+            }
+        }
+    }
+}
+
+// A Borsh encoded AdminAction waiting for its ETA
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct QueuedAction {
+    pub id: u64,
+    pub eta: i64,
+    pub data: Vec<u8>
+}
+
+impl QueuedAction {
+    pub const LEN: usize = U64_L + I64_L + VEC_L + MAX_ACTION_L;
+}
+
+#[account]
+pub struct AdminQueue {
+    pub config: Pubkey, // Pool config, or the multi-asset pool itself
+    pub next_id: u64,
+    pub bump: u8,
+    pub actions: Vec<QueuedAction>
+}
+
+impl AdminQueue {
+    pub const LEN: usize = 8 + PUBKEY_L + U64_L + U8_L + VEC_L + (QueuedAction::LEN * MAX_QUEUED_ACTIONS);
+
+    pub fn init(
+        &mut self,
+        config: Pubkey,
+        bump: u8
+    ) {
+        self.config = config;
+        self.next_id = 0;
+        self.bump = bump;
+        self.actions = vec![];
+    }
+
+    // Queues an action and returns its id
+    pub fn push(
+        &mut self,
+        data: Vec<u8>,
+        eta: i64
+    ) -> Result<u64> {
+        require!(data.len() <= MAX_ACTION_L, AmmError::InvalidAction);
+        require!(self.actions.len() < MAX_QUEUED_ACTIONS, AmmError::ActionQueueFull);
+        let id = self.next_id;
+        self.next_id = id.checked_add(1).ok_or(AmmError::Overflow)?;
+        self.actions.push(QueuedAction { id, eta, data });
+        Ok(id)
+    }
+
+    pub fn remove(
+        &mut self,
+        id: u64
+    ) -> Result<QueuedAction> {
+        let index = self.actions.iter().position(|a| a.id == id).ok_or(AmmError::ActionNotFound)?;
+        Ok(self.actions.remove(index))
+    }
+
+    // Removes the oldest queued copy of an action once it's past its ETA
+    pub fn take(
+        &mut self,
+        data: &[u8],
+        now: i64
+    ) -> Result<()> {
+        let index = self.actions.iter().position(|a| a.data.as_slice() == data).ok_or(AmmError::ActionNotFound)?;
+        require!(now >= self.actions[index].eta, AmmError::ActionNotReady);
+        self.actions.remove(index);
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use crate::constants::*;
+use anchor_lang::prelude::*;
+
+// Blocklist entry of a wallet, barred from trading or depositing in a pool while it exists
+#[account]
+pub struct Blocked {
+    pub config: Pubkey,
+    pub wallet: Pubkey,
+    pub bump: u8
+}
+
+impl Blocked {
+    pub const LEN: usize = 8 + (PUBKEY_L * 2) + U8_L;
+
+    pub fn init(
+        &mut self,
+        config: Pubkey,
+        wallet: Pubkey,
+        bump: u8
+    ) {
+        self.config = config;
+        self.wallet = wallet;
+        self.bump = bump;
+    }
+}
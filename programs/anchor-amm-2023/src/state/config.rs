@@ -1,4 +1,10 @@
 use crate::constants::*;
+use crate::errors::AmmError;
+use crate::helpers::math::{sqrt, mul_wide};
+use crate::state::observation::Observation;
+use crate::state::member::AccessMode;
+use crate::state::role::Role;
+use crate::helpers::access::Gated;
 use anchor_lang::prelude::*;
 
 #[account]
@@ -11,11 +17,46 @@ pub struct Config {
     pub locked: bool,
     pub auth_bump: u8,
     pub config_bump: u8,
-    pub lp_bump: u8
+    pub lp_bump: u8,
+    pub oracle: Option<Pubkey>,   // Reference price account (oracle mode)
+    pub oracle_program: Pubkey,   // Program that must own the oracle account
+    pub max_staleness: i64,       // Max age of oracle price in seconds
+    pub max_confidence: u16,      // Max oracle confidence interval in basis points
+    pub spread: u16,              // Spread around oracle price in basis points
+    pub flash_fee: u16,           // Flash loan fee in basis points
+    pub flash_loan_amount: u64,   // Principal of the open flash loan (0 if none)
+    pub flash_loan_is_x: bool,    // Side of the open flash loan
+    pub price_x_cumulative: u128, // Sum of Q64.64 price of X in Y over time (wraps)
+    pub price_y_cumulative: u128, // Sum of Q64.64 price of Y in X over time (wraps)
+    pub liquidity_cumulative: u128, // Sum of sqrt(x * y) over time (wraps)
+    pub last_update_ts: i64,      // Timestamp of the last cumulative price update
+    pub reserve_x: u64,           // X reserves as of the last cumulative price update
+    pub reserve_y: u64,           // Y reserves as of the last cumulative price update
+    pub max_price_move: u16,      // Max spot price move per window in basis points (0 disables)
+    pub price_window: i64,        // Circuit breaker window length in seconds
+    pub pause_on_break: bool,     // Let anyone pause swaps once reserves break the band
+    pub swaps_paused: bool,       // Set by the circuit breaker, cleared by unlock
+    pub window_start_ts: i64,     // Start of the current circuit breaker window
+    pub window_price: u128,       // Q64.64 spot price at the start of the window
+    pub max_outflow: u16,         // Max outflow per window in basis points of reserves (0 disables)
+    pub outflow_window: u64,      // Outflow rate limit window length in slots
+    pub outflow_window_start: u64, // Slot the current outflow window started at
+    pub outflow_reserve_x: u64,   // X reserves at the start of the outflow window
+    pub outflow_reserve_y: u64,   // Y reserves at the start of the outflow window
+    pub outflow_x: u64,           // X paid out in the current window
+    pub outflow_y: u64,           // Y paid out in the current window
+    pub max_reserve_x: u64,       // Cap on X reserves after a deposit (0 disables)
+    pub max_reserve_y: u64,       // Cap on Y reserves after a deposit (0 disables)
+    pub access_mode: AccessMode,  // Who can deposit and trade
+    pub gate_mint: Option<Pubkey>, // Holding this token proves membership in gated pools
+    pub compliance_authority: Option<Pubkey>, // Can block and unblock wallets
+    pub guardian: Option<Pubkey>, // Can pause the pool
+    pub fee_manager: Option<Pubkey>, // Can change fees
+    pub timelock_delay: i64       // Seconds admin actions wait in the queue (0 applies them directly)
 }
 
 impl Config {
-    pub const LEN: usize = 8 + U64_L + OPTION_L + (PUBKEY_L * 3) + U16_L + BOOL_L + (U8_L * 3);
+    pub const LEN: usize = 8 + (U64_L * 12) + (OPTION_L * 6) + (PUBKEY_L * 9) + (U16_L * 6) + (BOOL_L * 4) + (U8_L * 4) + (I64_L * 5) + (U128_L * 4);
 
     pub fn init(
         &mut self, 
@@ -37,5 +78,209 @@ impl Config {
         self.auth_bump = auth_bump;
         self.config_bump = config_bump;
         self.lp_bump = lp_bump;
+        self.oracle = None;
+        self.oracle_program = Pubkey::default();
+        self.max_staleness = 0;
+        self.max_confidence = 0;
+        self.spread = 0;
+        self.flash_fee = 0;
+        self.flash_loan_amount = 0;
+        self.flash_loan_is_x = false;
+        self.price_x_cumulative = 0;
+        self.price_y_cumulative = 0;
+        self.liquidity_cumulative = 0;
+        self.last_update_ts = 0;
+        self.reserve_x = 0;
+        self.reserve_y = 0;
+        self.max_price_move = 0;
+        self.price_window = 0;
+        self.pause_on_break = false;
+        self.swaps_paused = false;
+        self.window_start_ts = 0;
+        self.window_price = 0;
+        self.max_outflow = 0;
+        self.outflow_window = 0;
+        self.outflow_window_start = 0;
+        self.outflow_reserve_x = 0;
+        self.outflow_reserve_y = 0;
+        self.outflow_x = 0;
+        self.outflow_y = 0;
+        self.max_reserve_x = 0;
+        self.max_reserve_y = 0;
+        self.access_mode = AccessMode::Open;
+        self.gate_mint = None;
+        self.compliance_authority = None;
+        self.guardian = None;
+        self.fee_manager = None;
+        self.timelock_delay = 0;
     }
-}
\ No newline at end of file
+
+    // The admin passes every role check, other keys only the role they were given
+    pub fn check_role(
+        &self,
+        role: Role,
+        key: Pubkey
+    ) -> Result<()> {
+        let holder = match role {
+            Role::Admin => None,
+            Role::Guardian => self.guardian,
+            Role::FeeManager => self.fee_manager,
+            Role::Compliance => self.compliance_authority
+        };
+        role.check(self.authority, holder, key)
+    }
+
+    // Cumulative values as of `now`. Reserves only change through updates, so the stored ones
+    // held since the last update and tokens sent straight to the vaults can't move the price.
+    pub fn observation(
+        &self,
+        now: i64
+    ) -> Observation {
+        let mut observation = Observation {
+            timestamp: now,
+            price_x_cumulative: self.price_x_cumulative,
+            price_y_cumulative: self.price_y_cumulative,
+            liquidity_cumulative: self.liquidity_cumulative
+        };
+        let elapsed = now.saturating_sub(self.last_update_ts);
+        if self.last_update_ts == 0 || elapsed <= 0 || self.reserve_x == 0 || self.reserve_y == 0 {
+            return observation;
+        }
+        let elapsed = elapsed as u128;
+        let (x, y) = (self.reserve_x as u128, self.reserve_y as u128);
+        observation.price_x_cumulative = observation.price_x_cumulative.wrapping_add(((y << 64) / x).wrapping_mul(elapsed));
+        observation.price_y_cumulative = observation.price_y_cumulative.wrapping_add(((x << 64) / y).wrapping_mul(elapsed));
+        observation.liquidity_cumulative = observation.liquidity_cumulative.wrapping_add(sqrt(x * y).wrapping_mul(elapsed));
+        observation
+    }
+
+    // Must be called with the reserves after they change, once an instruction has moved its tokens
+    pub fn update_twap(
+        &mut self,
+        x: u64,
+        y: u64,
+        now: i64
+    ) -> Observation {
+        let observation = self.observation(now);
+        self.price_x_cumulative = observation.price_x_cumulative;
+        self.price_y_cumulative = observation.price_y_cumulative;
+        self.liquidity_cumulative = observation.liquidity_cumulative;
+        self.last_update_ts = now;
+        self.reserve_x = x;
+        self.reserve_y = y;
+        observation
+    }
+
+    // Q64.64 price of X in Y
+    pub fn spot_price(
+        x: u64,
+        y: u64
+    ) -> u128 {
+        match x {
+            0 => 0,
+            _ => ((y as u128) << 64) / x as u128
+        }
+    }
+
+    // Checks a trade's post-trade price against the price at the start of the current window,
+    // starting a new window from the pre-trade price once the old one has expired
+    pub fn check_price_band(
+        &mut self,
+        x: u64,
+        y: u64,
+        x_after: u64,
+        y_after: u64,
+        now: i64
+    ) -> bool {
+        if self.max_price_move == 0 {
+            return true;
+        }
+        if self.window_price == 0 || now.saturating_sub(self.window_start_ts) >= self.price_window {
+            self.window_start_ts = now;
+            self.window_price = Self::spot_price(x, y);
+        }
+        self.in_price_band(x_after, y_after)
+    }
+
+    // Whether reserves moved outside the band of a window that is still open. Trades can't do
+    // that, but transfers straight into the vaults can once a deposit or withdrawal records them.
+    pub fn price_band_broken(
+        &self,
+        x: u64,
+        y: u64,
+        now: i64
+    ) -> bool {
+        if self.max_price_move == 0 || self.window_price == 0 || now.saturating_sub(self.window_start_ts) >= self.price_window {
+            return false;
+        }
+        !self.in_price_band(x, y)
+    }
+
+    pub fn in_price_band(
+        &self,
+        x: u64,
+        y: u64
+    ) -> bool {
+        let moved = Self::spot_price(x, y).abs_diff(self.window_price);
+        mul_wide(moved, 10000) <= mul_wide(self.window_price, self.max_price_move as u128)
+    }
+
+    // Adds to the current window's outflows, which are capped against the reserves at its start
+    pub fn record_outflow(
+        &mut self,
+        x: u64,
+        y: u64,
+        out_x: u64,
+        out_y: u64,
+        slot: u64
+    ) -> Result<()> {
+        if self.max_outflow == 0 {
+            return Ok(());
+        }
+        if self.outflow_window_start == 0 || slot.saturating_sub(self.outflow_window_start) >= self.outflow_window {
+            self.outflow_window_start = slot;
+            self.outflow_reserve_x = x;
+            self.outflow_reserve_y = y;
+            self.outflow_x = 0;
+            self.outflow_y = 0;
+        }
+        self.outflow_x = self.outflow_x.checked_add(out_x).ok_or(AmmError::Overflow)?;
+        self.outflow_y = self.outflow_y.checked_add(out_y).ok_or(AmmError::Overflow)?;
+
+        let max = self.max_outflow as u128;
+        require!(
+            self.outflow_x as u128 * 10000 <= self.outflow_reserve_x as u128 * max
+                && self.outflow_y as u128 * 10000 <= self.outflow_reserve_y as u128 * max,
+            AmmError::OutflowLimitExceeded
+        );
+        Ok(())
+    }
+
+    // Rejects deposits that would take either reserve over its cap
+    pub fn check_deposit_cap(
+        &self,
+        x: u64,
+        y: u64,
+        deposit_x: u64,
+        deposit_y: u64
+    ) -> Result<()> {
+        let x = x.checked_add(deposit_x).ok_or(AmmError::Overflow)?;
+        let y = y.checked_add(deposit_y).ok_or(AmmError::Overflow)?;
+        require!(
+            (self.max_reserve_x == 0 || x <= self.max_reserve_x)
+                && (self.max_reserve_y == 0 || y <= self.max_reserve_y),
+            AmmError::DepositCapExceeded
+        );
+        Ok(())
+    }
+}
+
+impl Gated for Config {
+    fn access_mode(&self) -> AccessMode {
+        self.access_mode
+    }
+
+    fn gate_mint(&self) -> Option<Pubkey> {
+        self.gate_mint
+    }
+}
@@ -0,0 +1,156 @@
+use crate::constants::*;
+use crate::errors::AmmError;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RewardStream {
+    pub mint: Pubkey,
+    pub emission_rate: u64,     // Reward tokens emitted per second
+    pub funded: u64,            // Total reward tokens funded
+    pub emitted: u64,           // Total reward tokens emitted, never more than funded
+    pub reward_per_share: u128, // Rewards per staked LP token, scaled by REWARD_PRECISION
+    pub start_ts: i64,          // Unix timestamp emissions start at
+    pub end_ts: i64             // Unix timestamp emissions stop at
+}
+
+impl RewardStream {
+    pub const LEN: usize = PUBKEY_L + (U64_L * 3) + U128_L + (I64_L * 2);
+
+    // Seconds between from and to that fall inside the stream's window
+    pub fn active_time(
+        &self,
+        from: i64,
+        to: i64
+    ) -> i64 {
+        std::cmp::min(to, self.end_ts).saturating_sub(std::cmp::max(from, self.start_ts)).max(0)
+    }
+}
+
+// Rewards for staking a pool's LP tokens, paid out of up to MAX_REWARD_STREAMS vaults
+#[account]
+pub struct Farm {
+    pub config: Pubkey,
+    pub last_update_ts: i64,
+    pub total_staked: u64,
+    pub bump: u8,
+    pub streams: Vec<RewardStream>
+}
+
+impl Farm {
+    pub const LEN: usize = 8 + PUBKEY_L + I64_L + U64_L + U8_L + VEC_L + (RewardStream::LEN * MAX_REWARD_STREAMS);
+
+    pub fn init(
+        &mut self,
+        config: Pubkey,
+        bump: u8,
+        now: i64
+    ) {
+        self.config = config;
+        self.last_update_ts = now;
+        self.total_staked = 0;
+        self.bump = bump;
+        self.streams = vec![];
+    }
+
+    // Call update first so the new stream doesn't accrue for time before it existed
+    pub fn add_stream(
+        &mut self,
+        mint: Pubkey,
+        emission_rate: u64,
+        start_ts: i64,
+        end_ts: i64
+    ) -> Result<()> {
+        require!(self.streams.len() < MAX_REWARD_STREAMS, AmmError::TooManyRewardStreams);
+        require!(self.streams.iter().all(|s| s.mint != mint), AmmError::InvalidToken);
+        require!(end_ts > start_ts, AmmError::InvalidAmount);
+        self.streams.push(RewardStream {
+            mint,
+            emission_rate,
+            start_ts,
+            end_ts,
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    pub fn stream_index(
+        &self,
+        mint: Pubkey
+    ) -> Result<usize> {
+        self.streams.iter().position(|s| s.mint == mint).ok_or(error!(AmmError::InvalidToken))
+    }
+
+    // Accrue emissions since the last update to everyone staked, counting only time inside each
+    // stream's window and up to what it is funded with
+    pub fn update(
+        &mut self,
+        now: i64
+    ) -> Result<()> {
+        if self.total_staked > 0 {
+            for stream in self.streams.iter_mut() {
+                let elapsed = stream.active_time(self.last_update_ts, now) as u128;
+                if elapsed == 0 {
+                    continue;
+                }
+                let available = (stream.funded - stream.emitted) as u128;
+                let rewards = std::cmp::min((stream.emission_rate as u128).saturating_mul(elapsed), available);
+                stream.emitted += rewards as u64;
+                stream.reward_per_share = stream.reward_per_share
+                    .checked_add(rewards * REWARD_PRECISION / self.total_staked as u128)
+                    .ok_or(AmmError::Overflow)?;
+            }
+        }
+        self.last_update_ts = now;
+        Ok(())
+    }
+}
+
+// A user's LP stake in a farm, with reward accounting per stream
+#[account]
+pub struct StakeInfo {
+    pub owner: Pubkey,
+    pub farm: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+    pub reward_debts: Vec<u128>, // Rewards already accounted for at the current amount
+    pub pending: Vec<u64>        // Rewards settled but not yet claimed
+}
+
+impl StakeInfo {
+    pub const LEN: usize = 8 + (PUBKEY_L * 2) + U64_L + U8_L + (VEC_L * 2) + ((U128_L + U64_L) * MAX_REWARD_STREAMS);
+
+    // Move rewards earned since the last settlement into pending
+    pub fn settle(
+        &mut self,
+        streams: &[RewardStream]
+    ) -> Result<()> {
+        // Streams added since the last settlement start from zero
+        self.reward_debts.resize(streams.len(), 0);
+        self.pending.resize(streams.len(), 0);
+        for (i, stream) in streams.iter().enumerate() {
+            let earned = self.earned(stream.reward_per_share)?;
+            let pending = earned.checked_sub(self.reward_debts[i]).ok_or(AmmError::Underflow)?;
+            self.pending[i] = u64::try_from(pending + self.pending[i] as u128).map_err(|_| AmmError::Overflow)?;
+            self.reward_debts[i] = earned;
+        }
+        Ok(())
+    }
+
+    // Call after changing amount, once settled
+    pub fn reset_debts(
+        &mut self,
+        streams: &[RewardStream]
+    ) -> Result<()> {
+        for (i, stream) in streams.iter().enumerate() {
+            self.reward_debts[i] = self.earned(stream.reward_per_share)?;
+        }
+        Ok(())
+    }
+
+    pub fn earned(
+        &self,
+        reward_per_share: u128
+    ) -> Result<u128> {
+        Ok((self.amount as u128).checked_mul(reward_per_share).ok_or(AmmError::Overflow)? / REWARD_PRECISION)
+    }
+}
@@ -0,0 +1,60 @@
+use crate::constants::*;
+use crate::errors::AmmError;
+use crate::helpers::math::sqrt;
+use anchor_lang::prelude::*;
+
+// LP tokens escrowed by a user until unlock_ts
+#[account]
+pub struct LpLock {
+    pub owner: Pubkey,
+    pub config: Pubkey,
+    pub amount: u64,
+    pub unlock_ts: i64,
+    pub liquidity_per_lp: u128, // Q64.64 sqrt(x * y) per LP token the locked principal is worth
+    pub bump: u8
+}
+
+impl LpLock {
+    pub const LEN: usize = 8 + (PUBKEY_L * 2) + U64_L + I64_L + U128_L + U8_L;
+
+    // Fees grow sqrt(x * y) without minting LP, so this only goes up between claims
+    pub fn liquidity_per_lp(
+        x: u64,
+        y: u64,
+        supply: u64
+    ) -> Result<u128> {
+        require!(supply > 0, AmmError::NoLiquidityInPool);
+        Ok((sqrt(x as u128 * y as u128) << 64) / supply as u128)
+    }
+
+    // Blend in newly locked LP so fees earned by the existing lock stay claimable. Liquidity
+    // per LP only grows, so a snapshot below the lock's baseline can't be genuine and the
+    // baseline is kept. The blend rounds up so it never credits fees that weren't earned.
+    pub fn add(
+        &mut self,
+        amount: u64,
+        liquidity_per_lp: u128
+    ) -> Result<()> {
+        let liquidity_per_lp = liquidity_per_lp.max(self.liquidity_per_lp);
+        let total = self.amount.checked_add(amount).ok_or(AmmError::Overflow)?;
+        let principal = (self.amount as u128).checked_mul(self.liquidity_per_lp)
+            .and_then(|p| p.checked_add((amount as u128).checked_mul(liquidity_per_lp)?))
+            .ok_or(AmmError::Overflow)?;
+        self.liquidity_per_lp = (principal + total as u128 - 1) / total as u128;
+        self.amount = total;
+        Ok(())
+    }
+
+    // LP tokens representing fees earned since the lock or last claim, rounded down
+    pub fn fee_lp(
+        &self,
+        liquidity_per_lp: u128
+    ) -> Result<u64> {
+        if liquidity_per_lp <= self.liquidity_per_lp {
+            return Ok(0);
+        }
+        let principal = (self.amount as u128).checked_mul(self.liquidity_per_lp).ok_or(AmmError::Overflow)?;
+        let kept = (principal + liquidity_per_lp - 1) / liquidity_per_lp;
+        Ok(self.amount - kept as u64)
+    }
+}
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+// Metaplex metadata for a pool's LP mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LpMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String
+}
+
+impl LpMetadata {
+    // "AMM X-Y LP", with each side shortened to the start of its mint address
+    pub fn for_pair(
+        mint_x: &Pubkey,
+        mint_y: &Pubkey
+    ) -> Self {
+        let (x, y) = (mint_x.to_string(), mint_y.to_string());
+        let (x, y) = (&x[..4], &y[..4]);
+        Self {
+            name: format!("AMM {}-{} LP", x, y),
+            symbol: format!("{}-{}", x, y),
+            uri: String::new()
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::constants::*;
+use anchor_lang::prelude::*;
+
+// Who can provide liquidity and trade in a pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AccessMode {
+    Open,       // Anyone
+    LpGated,    // Only members can deposit
+    FullyGated  // Only members can deposit or trade
+}
+
+impl AccessMode {
+    pub fn is_gated(
+        &self,
+        is_trade: bool
+    ) -> bool {
+        match self {
+            AccessMode::Open => false,
+            AccessMode::LpGated => !is_trade,
+            AccessMode::FullyGated => true
+        }
+    }
+}
+
+// Allowlist entry of a wallet in a permissioned pool
+#[account]
+pub struct Member {
+    pub config: Pubkey,
+    pub wallet: Pubkey,
+    pub bump: u8
+}
+
+impl Member {
+    pub const LEN: usize = 8 + (PUBKEY_L * 2) + U8_L;
+
+    pub fn init(
+        &mut self,
+        config: Pubkey,
+        wallet: Pubkey,
+        bump: u8
+    ) {
+        self.config = config;
+        self.wallet = wallet;
+        self.bump = bump;
+    }
+}
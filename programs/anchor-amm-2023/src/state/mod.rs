@@ -1,2 +1,24 @@
 pub mod config;
-pub use config::*;
\ No newline at end of file
+pub use config::*;
+pub mod oracle;
+pub use oracle::*;
+pub mod multi_pool;
+pub use multi_pool::*;
+pub mod observation;
+pub use observation::*;
+pub mod farm;
+pub use farm::*;
+pub mod lp_lock;
+pub use lp_lock::*;
+pub mod position;
+pub use position::*;
+pub mod lp_metadata;
+pub use lp_metadata::*;
+pub mod member;
+pub use member::*;
+pub mod blocked;
+pub use blocked::*;
+pub mod role;
+pub use role::*;
+pub mod admin_action;
+pub use admin_action::*;
\ No newline at end of file
@@ -0,0 +1,181 @@
+use crate::constants::*;
+use crate::errors::AmmError;
+use crate::helpers::math::*;
+use crate::helpers::access::Gated;
+use crate::state::member::AccessMode;
+use crate::state::role::Role;
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+#[account]
+pub struct MultiPool {
+    pub seed: u64,
+    pub authority: Option<Pubkey>, // Admin, holds every role
+    pub guardian: Option<Pubkey>, // Can pause the pool
+    pub fee_manager: Option<Pubkey>, // Can change fees
+    pub compliance_authority: Option<Pubkey>, // Can block and unblock wallets
+    pub timelock_delay: i64,      // Seconds admin actions wait in the queue, 0 applies them directly
+    pub fee: u16,                 // Swap fee in basis points
+    pub locked: bool,
+    pub pool_bump: u8,
+    pub lp_bump: u8,
+    pub access_mode: AccessMode,  // Who can deposit and trade
+    pub gate_mint: Option<Pubkey>, // Holding this token proves membership in gated pools
+    pub mints: Vec<Pubkey>,       // Token mints
+    pub vaults: Vec<Pubkey>,      // Pool owned ATAs, same order as mints
+    pub weights: Vec<u16>         // Asset weights in basis points, summing to 10000
+}
+
+impl MultiPool {
+    pub fn len(n: usize) -> usize {
+        8 + U64_L + (OPTION_L * 5) + (PUBKEY_L * 5) + I64_L + U16_L + BOOL_L + (U8_L * 3) + (VEC_L * 3) + (n * (PUBKEY_L * 2 + U16_L))
+    }
+
+    pub fn init(
+        &mut self,
+        seed: u64,
+        authority: Option<Pubkey>,
+        fee: u16,
+        mints: Vec<Pubkey>,
+        vaults: Vec<Pubkey>,
+        weights: Vec<u16>,
+        pool_bump: u8,
+        lp_bump: u8
+    ) {
+        self.seed = seed;
+        self.authority = authority;
+        self.guardian = None;
+        self.fee_manager = None;
+        self.compliance_authority = None;
+        self.timelock_delay = 0;
+        self.fee = fee;
+        self.locked = false;
+        self.access_mode = AccessMode::Open;
+        self.gate_mint = None;
+        self.mints = mints;
+        self.vaults = vaults;
+        self.weights = weights;
+        self.pool_bump = pool_bump;
+        self.lp_bump = lp_bump;
+    }
+
+    pub fn check_role(
+        &self,
+        role: Role,
+        key: Pubkey
+    ) -> Result<()> {
+        let holder = match role {
+            Role::Admin => None,
+            Role::Guardian => self.guardian,
+            Role::FeeManager => self.fee_manager,
+            Role::Compliance => self.compliance_authority
+        };
+        role.check(self.authority, holder, key)
+    }
+
+    // Deserialize the pool's vaults from remaining accounts, checked against the stored list
+    pub fn load_vaults<'info>(
+        &self,
+        accounts: &[AccountInfo<'info>]
+    ) -> Result<Vec<Account<'info, TokenAccount>>> {
+        require!(accounts.len() >= self.vaults.len(), AmmError::InvalidVault);
+        self.vaults.iter().zip(accounts.iter()).map(|(vault, info)| {
+            require_keys_eq!(*vault, info.key(), AmmError::InvalidVault);
+            Account::<TokenAccount>::try_from(info)
+        }).collect()
+    }
+
+    pub fn check_index(
+        &self,
+        index: u8
+    ) -> Result<usize> {
+        require!((index as usize) < self.mints.len(), AmmError::InvalidIndex);
+        Ok(index as usize)
+    }
+
+    fn weight(
+        &self,
+        index: usize
+    ) -> u128 {
+        self.weights[index] as u128 * WAD / 10000
+    }
+
+    // Fee charged on the share of a single sided deposit or withdraw that isn't proportional
+    fn single_fee_complement(
+        &self,
+        index: usize
+    ) -> Result<u128> {
+        let fee = self.fee as u128 * WAD / 10000;
+        Ok(WAD - mul_up(fee, WAD - self.weight(index))?)
+    }
+
+    // V = prod(b_i^w_i), the weighted geometric mean of the balances
+    pub fn invariant(
+        &self,
+        balances: &[u64]
+    ) -> Result<u64> {
+        let terms = balances.iter().enumerate().map(|(i, b)| {
+            Ok(((*b as u128).checked_mul(WAD).ok_or(AmmError::Overflow)?, self.weight(i)))
+        }).collect::<Result<Vec<(u128, u128)>>>()?;
+        u64::try_from(weighted_product_down(&terms)? / WAD).map_err(|_| error!(AmmError::Overflow))
+    }
+
+    // out = b_o * (1 - (b_i / (b_i + a))^(w_i / w_o))
+    pub fn swap_out(
+        &self,
+        in_index: usize,
+        out_index: usize,
+        balance_in: u64,
+        balance_out: u64,
+        amount: u64
+    ) -> Result<u64> {
+        let amount = (amount as u128) * (10000 - self.fee as u128) / 10000;
+        let base = div_up(balance_in as u128, balance_in as u128 + amount)?;
+        let exponent = div_down(self.weight(in_index), self.weight(out_index))?;
+        let power = pow_up(base, exponent)?;
+        let out = mul_down(balance_out as u128, WAD.saturating_sub(power))?;
+        u64::try_from(out).map_err(|_| error!(AmmError::Overflow))
+    }
+
+    // lp = supply * ((1 + a / b_i)^w_i - 1)
+    pub fn single_deposit_lp(
+        &self,
+        index: usize,
+        balance: u64,
+        supply: u64,
+        amount: u64
+    ) -> Result<u64> {
+        let amount = mul_down(amount as u128, self.single_fee_complement(index)?)?;
+        let ratio = div_down(balance as u128 + amount, balance as u128)?;
+        let power = pow_down(ratio, self.weight(index))?;
+        let lp = mul_down(supply as u128, power.saturating_sub(WAD))?;
+        u64::try_from(lp).map_err(|_| error!(AmmError::Overflow))
+    }
+
+    // out = b_i * (1 - (1 - lp / supply)^(1 / w_i))
+    pub fn single_withdraw_out(
+        &self,
+        index: usize,
+        balance: u64,
+        supply: u64,
+        lp: u64
+    ) -> Result<u64> {
+        require!(lp < supply, AmmError::InsufficientBalance);
+        let base = div_up((supply - lp) as u128, supply as u128)?;
+        let exponent = div_up(WAD, self.weight(index))?;
+        let power = pow_up(base, exponent)?;
+        let out = mul_down(balance as u128, WAD.saturating_sub(power))?;
+        let out = mul_down(out, self.single_fee_complement(index)?)?;
+        u64::try_from(out).map_err(|_| error!(AmmError::Overflow))
+    }
+}
+
+impl Gated for MultiPool {
+    fn access_mode(&self) -> AccessMode {
+        self.access_mode
+    }
+
+    fn gate_mint(&self) -> Option<Pubkey> {
+        self.gate_mint
+    }
+}
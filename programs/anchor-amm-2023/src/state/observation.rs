@@ -0,0 +1,143 @@
+use crate::constants::*;
+use crate::errors::AmmError;
+use anchor_lang::prelude::*;
+
+// Cumulative prices are Q64.64 and liquidity is sqrt(x * y), each summed over seconds. They wrap
+// on overflow, so a TWAP is (cumulative_b - cumulative_a) / (timestamp_b - timestamp_a), using
+// wrapping subtraction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct Observation {
+    pub timestamp: i64,
+    pub price_x_cumulative: u128,
+    pub price_y_cumulative: u128,
+    pub liquidity_cumulative: u128
+}
+
+impl Observation {
+    pub const LEN: usize = I64_L + (U128_L * 3);
+
+    // Linear interpolation towards a later observation
+    pub fn interpolate(
+        &self,
+        next: &Observation,
+        timestamp: i64
+    ) -> Observation {
+        let elapsed = (next.timestamp - self.timestamp) as u128;
+        if elapsed == 0 {
+            return *self;
+        }
+        let delta = (timestamp - self.timestamp) as u128;
+        let lerp = |a: u128, b: u128| a.wrapping_add((b.wrapping_sub(a) / elapsed).wrapping_mul(delta));
+        Observation {
+            timestamp,
+            price_x_cumulative: lerp(self.price_x_cumulative, next.price_x_cumulative),
+            price_y_cumulative: lerp(self.price_y_cumulative, next.price_y_cumulative),
+            liquidity_cumulative: lerp(self.liquidity_cumulative, next.liquidity_cumulative)
+        }
+    }
+}
+
+// Time-weighted averages over a window, prices as Q64.64
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct Twap {
+    pub price_x: u128,            // Average price of X in Y
+    pub price_y: u128,            // Average price of Y in X
+    pub liquidity: u128           // Average sqrt(x * y)
+}
+
+impl Twap {
+    // Averages between two observations, `start` being the earlier one
+    pub fn between(
+        start: &Observation,
+        end: &Observation
+    ) -> Result<Twap> {
+        require!(end.timestamp > start.timestamp, AmmError::InvalidAmount);
+        let elapsed = (end.timestamp - start.timestamp) as u128;
+        let average = |a: u128, b: u128| b.wrapping_sub(a) / elapsed;
+        Ok(Twap {
+            price_x: average(start.price_x_cumulative, end.price_x_cumulative),
+            price_y: average(start.price_y_cumulative, end.price_y_cumulative),
+            liquidity: average(start.liquidity_cumulative, end.liquidity_cumulative)
+        })
+    }
+}
+
+// Ring buffer of past observations for a pool, written whenever its reserves change
+#[account]
+pub struct Observations {
+    pub config: Pubkey,
+    pub bump: u8,
+    pub index: u16,        // Position of the most recent observation
+    pub cardinality: u16,  // Number of observations the account has room for
+    pub observations: Vec<Observation>
+}
+
+impl Observations {
+    pub fn len(cardinality: u16) -> usize {
+        8 + PUBKEY_L + U8_L + (U16_L * 2) + VEC_L + (Observation::LEN * cardinality as usize)
+    }
+
+    pub fn init(
+        &mut self,
+        config: Pubkey,
+        bump: u8
+    ) {
+        self.config = config;
+        self.bump = bump;
+        self.index = 0;
+        self.cardinality = 1;
+        self.observations = vec![];
+    }
+
+    // Grows into new room only once the newest entry is the last one, so the buffer stays in order
+    pub fn write(
+        &mut self,
+        observation: Observation
+    ) {
+        let index = self.index as usize;
+        match self.observations.get(index) {
+            None => {
+                self.observations.push(observation);
+                self.index = 0;
+                return;
+            },
+            // One observation per second is enough
+            Some(last) if last.timestamp >= observation.timestamp => return,
+            _ => {}
+        }
+
+        let next = index + 1;
+        if next < self.observations.len() {
+            self.observations[next] = observation;
+        } else if next < self.cardinality as usize {
+            self.observations.push(observation);
+        } else {
+            self.observations[0] = observation;
+            self.index = 0;
+            return;
+        }
+        self.index = next as u16;
+    }
+
+    // `latest` is the pool's state at its last update, which is never older than the buffer
+    pub fn observe(
+        &self,
+        timestamp: i64,
+        latest: Observation
+    ) -> Result<Observation> {
+        let len = self.observations.len();
+        let oldest = (self.index as usize + 1) % len.max(1);
+        let mut history = (0..len).map(|i| self.observations[(oldest + i) % len]).collect::<Vec<Observation>>();
+        if history.last().map_or(true, |o| o.timestamp < latest.timestamp) {
+            history.push(latest);
+        }
+
+        require!(history[0].timestamp <= timestamp, AmmError::ObservationTooOld);
+        for pair in history.windows(2) {
+            if timestamp < pair[1].timestamp {
+                return Ok(pair[0].interpolate(&pair[1], timestamp));
+            }
+        }
+        Ok(latest)
+    }
+}
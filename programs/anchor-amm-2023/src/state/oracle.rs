@@ -0,0 +1,85 @@
+use crate::constants::*;
+use crate::errors::AmmError;
+use anchor_lang::prelude::*;
+
+// Price account layout read from Config.oracle
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct OraclePrice {
+    pub price: u64,               // Price of X denominated in Y, scaled by 10^expo
+    pub conf: u64,                // Confidence interval, same scale as price
+    pub expo: u8,                 // Decimal places of price and conf
+    pub publish_time: i64         // Unix timestamp of last update
+}
+
+impl OraclePrice {
+    pub const LEN: usize = (U64_L * 2) + U8_L + I64_L;
+
+    pub fn load(
+        info: &AccountInfo,
+        oracle_program: &Pubkey
+    ) -> Result<OraclePrice> {
+        // Anyone can write this layout into an account they own, so only trust the oracle program
+        require_keys_eq!(*info.owner, *oracle_program, AmmError::InvalidOracle);
+        let data = info.try_borrow_data()?;
+        require!(data.len() >= OraclePrice::LEN, AmmError::InvalidOracle);
+        OraclePrice::deserialize(&mut &data[..OraclePrice::LEN]).map_err(|_| error!(AmmError::InvalidOracle))
+    }
+
+    pub fn validate(
+        &self,
+        now: i64,
+        max_staleness: i64,
+        max_confidence: u16
+    ) -> Result<()> {
+        require!(self.price > 0, AmmError::InvalidOracle);
+        // A price from the future can't be checked for staleness
+        require!(self.publish_time <= now, AmmError::InvalidOracle);
+        require!(now - self.publish_time <= max_staleness, AmmError::StaleOracle);
+        // Reject if conf / price > max_confidence / 10000
+        require!(
+            (self.conf as u128) * 10000 <= (self.price as u128) * (max_confidence as u128),
+            AmmError::OracleConfidence
+        );
+        Ok(())
+    }
+
+    // Output amount for a swap of `amount` priced around the oracle rate. Virtual
+    // reserves are centred on the oracle price with half of the pool's value on
+    // each side, so trades still pay price impact relative to pool depth.
+    pub fn swap_out(
+        &self,
+        x: u64,
+        y: u64,
+        is_x: bool,
+        amount: u64,
+        fee: u16,
+        spread: u16
+    ) -> Result<u64> {
+        let scale = 10u128.checked_pow(self.expo as u32).ok_or(AmmError::Overflow)?;
+        let price = self.price as u128;
+
+        // Total pool value denominated in Y, scaled by 10^expo
+        let value = (y as u128).checked_mul(scale).ok_or(AmmError::Overflow)?
+            .checked_add((x as u128).checked_mul(price).ok_or(AmmError::Overflow)?).ok_or(AmmError::Overflow)?;
+        let virtual_x = value.checked_div(price.checked_mul(2).ok_or(AmmError::Overflow)?).ok_or(AmmError::Underflow)?;
+        let virtual_y = value.checked_div(scale.checked_mul(2).ok_or(AmmError::Overflow)?).ok_or(AmmError::Underflow)?;
+
+        let amount = (amount as u128)
+            .checked_mul(10000 - fee as u128).ok_or(AmmError::Overflow)?
+            .checked_div(10000).ok_or(AmmError::Underflow)?;
+
+        let (reserve_in, reserve_out, balance_out) = match is_x {
+            true => (virtual_x, virtual_y, y),
+            false => (virtual_y, virtual_x, x)
+        };
+
+        let out = reserve_out
+            .checked_mul(amount).ok_or(AmmError::Overflow)?
+            .checked_div(reserve_in.checked_add(amount).ok_or(AmmError::Overflow)?).ok_or(AmmError::Underflow)?
+            .checked_mul(10000 - spread as u128).ok_or(AmmError::Overflow)?
+            .checked_div(10000).ok_or(AmmError::Underflow)?;
+
+        require!(out <= balance_out as u128, AmmError::InsufficientBalance);
+        Ok(out as u64)
+    }
+}
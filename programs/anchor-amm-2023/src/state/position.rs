@@ -0,0 +1,57 @@
+use crate::constants::*;
+use crate::errors::AmmError;
+use crate::helpers::math::sqrt;
+use anchor_lang::prelude::*;
+
+// Liquidity held on behalf of whoever holds the position's NFT
+#[account]
+pub struct Position {
+    pub config: Pubkey,
+    pub nft_mint: Pubkey,
+    pub deposited_x: u64,
+    pub deposited_y: u64,
+    pub lp_amount: u64,    // LP tokens held by the position
+    pub entry_ts: i64,
+    pub entry_k: u128,     // x * y of the pool after the deposit
+    pub entry_supply: u64, // LP supply after the deposit
+    pub bump: u8
+}
+
+impl Position {
+    pub const LEN: usize = 8 + (PUBKEY_L * 2) + (U64_L * 4) + I64_L + U128_L + U8_L;
+
+    // Splits a withdrawal into fees and principal, using the growth in sqrt(x * y) per LP token
+    // since entry. `liquidity_per_lp` is the current Q64.64 value.
+    pub fn fees(
+        &self,
+        liquidity_per_lp: u128,
+        withdrawn_x: u64,
+        withdrawn_y: u64
+    ) -> Result<(u64, u64)> {
+        let entry = (sqrt(self.entry_k) << 64) / self.entry_supply as u128;
+        if liquidity_per_lp <= entry || self.lp_amount == 0 {
+            return Ok((0, 0));
+        }
+        let amount = self.lp_amount as u128;
+        let principal = amount.checked_mul(entry).ok_or(AmmError::Overflow)?;
+        let fee_lp = amount - (principal + liquidity_per_lp - 1) / liquidity_per_lp;
+        Ok((
+            (withdrawn_x as u128 * fee_lp / amount) as u64,
+            (withdrawn_y as u128 * fee_lp / amount) as u64
+        ))
+    }
+
+    // Value of the deposit held outright minus the principal withdrawn, in Y at the price x:y
+    pub fn impermanent_loss(
+        &self,
+        x: u64,
+        y: u64,
+        principal_x: u64,
+        principal_y: u64
+    ) -> i64 {
+        let (x, y) = (x as u128, y as u128);
+        let held = self.deposited_x as u128 * y / x + self.deposited_y as u128;
+        let principal = principal_x as u128 * y / x + principal_y as u128;
+        (held as i128 - principal as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+}
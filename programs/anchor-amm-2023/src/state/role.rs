@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+use crate::errors::AmmError;
+
+// Admin permissions of a pool. The admin (the pool's authority) holds every role.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Admin,      // Everything, including unpausing
+    Guardian,   // Pause only
+    FeeManager, // Fee changes
+    Compliance  // Blocking and unblocking wallets
+}
+
+impl Role {
+    // Passes for the admin, or for the holder of this role if one is set
+    pub fn check(
+        self,
+        authority: Option<Pubkey>,
+        holder: Option<Pubkey>,
+        key: Pubkey
+    ) -> Result<()> {
+        require!(authority.is_some() || holder.is_some(), AmmError::NoAuthoritySet);
+        require!(authority == Some(key) || holder == Some(key), AmmError::InvalidAuthority);
+        Ok(())
+    }
+}
@@ -3,14 +3,21 @@ import { BN } from "@coral-xyz/anchor"
 import { Program } from "@coral-xyz/anchor";
 import { AnchorAmm2023, IDL } from "../target/types/anchor_amm_2023"
 import { ConstantProduct, LiquidityPair } from "constant-product-curve-wasm";
-import { PublicKey, Commitment, Keypair, SystemProgram } from "@solana/web3.js"
-import { ASSOCIATED_TOKEN_PROGRAM_ID as associatedTokenProgram, TOKEN_PROGRAM_ID as tokenProgram, createMint, createAccount, mintTo, getAssociatedTokenAddress, TOKEN_PROGRAM_ID } from "@solana/spl-token"
+import { PublicKey, Commitment, Keypair, SystemProgram, SYSVAR_INSTRUCTIONS_PUBKEY, SYSVAR_RENT_PUBKEY, Transaction } from "@solana/web3.js"
+import { ASSOCIATED_TOKEN_PROGRAM_ID as associatedTokenProgram, TOKEN_PROGRAM_ID as tokenProgram, createMint, createAccount, mintTo, transfer, getAccount, getAssociatedTokenAddress, getAssociatedTokenAddressSync, getOrCreateAssociatedTokenAccount, createWrappedNativeAccount, closeAccount, NATIVE_MINT, TOKEN_PROGRAM_ID } from "@solana/spl-token"
 import { randomBytes } from "crypto"
 import { assert } from "chai"
 import { ASSOCIATED_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
 
 const commitment: Commitment = "confirmed"; // processed, confirmed, finalized
 
+// Mock oracle accounts loaded from tests/fixtures (see Anchor.toml), owned by this program id
+const oracleProgram = new PublicKey("2hBPxj6iDLQ8TM12DmFCA9GW7t6sZcHQJRZWQ7JnkcUu");
+const oracle = new PublicKey("6JBN4xDavZiKGX8DBtx4iDio4A9VSVhJYGjf4GoZk7Kq");
+const oracle_stale = new PublicKey("3mEFb6oJRapAhBGEZV7EK5HX9JXX5zBunQBFkm9sZrK4");
+// Same price layout, but owned by the system program instead of the oracle program
+const oracle_unowned = new PublicKey("AmYFuWuamJvoWdNAj6ScPZph6pG2f4DHH3UXDEtJKvy6");
+
 describe("anchor-amm-2023", () => {
   // Configure the client to use the local cluster.
   anchor.setProvider(anchor.AnchorProvider.env());
@@ -32,6 +39,8 @@ describe("anchor-amm-2023", () => {
   let mint_x: PublicKey;
   let mint_y: PublicKey;
   let mint_lp = PublicKey.findProgramAddressSync([Buffer.from("lp"), config.toBuffer()], program.programId)[0];
+  const observationsFor = (config: PublicKey) => PublicKey.findProgramAddressSync([Buffer.from("observations"), config.toBuffer()], program.programId)[0];
+  const blockedFor = (config: PublicKey, wallet: PublicKey) => PublicKey.findProgramAddressSync([Buffer.from("blocked"), config.toBuffer(), wallet.toBuffer()], program.programId)[0];
 
   // ATAs
   let initializer_x_ata: PublicKey;
@@ -70,12 +79,22 @@ describe("anchor-amm-2023", () => {
   // // let c = new ConstantProduct(BigInt(30), BigInt(20), BigInt(20), 20);
   // // let res = c.swap(LiquidityPair.X, BigInt(1000), BigInt(200));
 
+  // LP token metadata, named after the pair unless given a name
+  const tokenMetadataProgram = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+  const lpMetadataFor = (mint: PublicKey) => PublicKey.findProgramAddressSync([Buffer.from("metadata"), tokenMetadataProgram.toBuffer(), mint.toBuffer()], tokenMetadataProgram)[0];
+  // Name is the first string in the account, after the key, update authority and mint
+  const lpMetadataName = async (mint: PublicKey) => {
+    const data = (await anchor.getProvider().connection.getAccountInfo(lpMetadataFor(mint))).data;
+    return data.subarray(69, 69 + data.readUInt32LE(65)).toString().replace(/\0/g, "");
+  };
+
   it("Initialize", async () => {
     try {
       const tx = await program.methods.initialize(
         seed,
         0,
-        initializer.publicKey
+        initializer.publicKey,
+        null
       )
       .accounts({
         auth,
@@ -85,7 +104,12 @@ describe("anchor-amm-2023", () => {
         mintLp: mint_lp,
         vaultX: vault_x_ata,
         vaultY: vault_y_ata,
+        vaultLp: vault_lp_ata,
         config,
+        observations: observationsFor(config),
+        lpMetadata: lpMetadataFor(mint_lp),
+        tokenMetadataProgram,
+        rent: SYSVAR_RENT_PUBKEY,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId
@@ -100,6 +124,35 @@ describe("anchor-amm-2023", () => {
     }
   });
 
+  it("Update LP metadata", async () => {
+    try {
+      assert.equal(await lpMetadataName(mint_lp), `AMM ${mint_x.toBase58().slice(0, 4)}-${mint_y.toBase58().slice(0, 4)} LP`);
+      const tx = await program.methods.updateLpMetadata({
+        name: "AMM X-Y LP",
+        symbol: "XY-LP",
+        uri: "https://example.com/lp.json"
+      })
+      .accountsStrict({
+        user: initializer.publicKey,
+        mintLp: mint_lp,
+        lpMetadata: lpMetadataFor(mint_lp),
+        auth,
+        adminQueue: null,
+        config,
+        tokenMetadataProgram
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      assert.equal(await lpMetadataName(mint_lp), "AMM X-Y LP");
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
   it("Lock", async () => {
     try {
       const tx = await program.methods.lock()
@@ -182,13 +235,37 @@ describe("anchor-amm-2023", () => {
     }
   });
 
-  it("Deposit", async () => {
+  it("Fail to make a first deposit below the minimum liquidity", async () => {
     try {
       const tx = await program.methods.deposit(
-        new BN(20),
+        new BN(1),
         new BN(20),
         new BN(30),
-        new BN(Math.floor(new Date().getTime()/1000) + 600)
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(depositAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("First deposit should lock the minimum liquidity", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "LiquidityLessThanMinimum") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Deposit", async () => {
+    try {
+      // The first deposit mints sqrt(20_000 * 30_000) = 24_494 LP and locks 1_000 of it
+      const tx = await program.methods.deposit(
+        new BN(20_000),
+        new BN(20_000),
+        new BN(30_000),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
       )
       .accountsStrict({
         auth,
@@ -201,7 +278,16 @@ describe("anchor-amm-2023", () => {
         userLp: initializer_lp_ata,
         vaultX: vault_x_ata,
         vaultY: vault_y_ata,
+        vaultLp: vault_lp_ata,
+        recipientLp: null,
         config,
+        observations: observationsFor(config),
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedLp: blockedFor(config, initializer.publicKey),
+        member: null,
+        gateToken: null,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId
@@ -224,9 +310,10 @@ describe("anchor-amm-2023", () => {
     try {
       const tx = await program.methods.swap(
         true,
-        new BN(5),
-        new BN(6),
-        new BN(Math.floor(new Date().getTime()/1000) + 600)
+        new BN(5_000),
+        new BN(6_000),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
       )
       .accountsStrict({
         auth,
@@ -237,7 +324,16 @@ describe("anchor-amm-2023", () => {
         userY: initializer_y_ata,
         vaultX: vault_x_ata,
         vaultY: vault_y_ata,
+        recipient: null,
         config,
+        observations: observationsFor(config),
+        oracle: null,
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedRecipient: null,
+        member: null,
+        gateToken: null,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId
@@ -260,9 +356,10 @@ describe("anchor-amm-2023", () => {
     try {
       const tx = await program.methods.swap(
         false,
-        new BN(6),
-        new BN(5),
-        new BN(Math.floor(new Date().getTime()/1000) + 600)
+        new BN(6_000),
+        new BN(5_000),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
       )
       .accountsStrict({
         auth,
@@ -273,7 +370,16 @@ describe("anchor-amm-2023", () => {
         userY: initializer_y_ata,
         vaultX: vault_x_ata,
         vaultY: vault_y_ata,
+        recipient: null,
         config,
+        observations: observationsFor(config),
+        oracle: null,
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedRecipient: null,
+        member: null,
+        gateToken: null,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId
@@ -295,10 +401,57 @@ describe("anchor-amm-2023", () => {
   it("Withdraw", async () => {
     try {
       const tx = await program.methods.withdraw(
+        new BN(20_000),
+        new BN(16_000),
+        new BN(24_000),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict({
+        auth,
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp,
+        userX: initializer_x_ata,
+        userY: initializer_y_ata,
+        userLp: initializer_lp_ata,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        recipientX: null,
+        recipientY: null,
+        config,
+        observations: observationsFor(config),
+        blocked: null,
+        blockedReceiver: null,
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      console.error(e);
+      if(err.error.errorCode.code !== "InvalidAuthority") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Deposit into oracle pool", async () => {
+    try {
+      const tx = await program.methods.deposit(
         new BN(20),
         new BN(20),
         new BN(30),
-        new BN(Math.floor(new Date().getTime()/1000) + 600)
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
       )
       .accountsStrict({
         auth,
@@ -311,7 +464,16 @@ describe("anchor-amm-2023", () => {
         userLp: initializer_lp_ata,
         vaultX: vault_x_ata,
         vaultY: vault_y_ata,
+        vaultLp: vault_lp_ata,
+        recipientLp: null,
         config,
+        observations: observationsFor(config),
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedLp: blockedFor(config, initializer.publicKey),
+        member: null,
+        gateToken: null,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId
@@ -320,15 +482,2458 @@ describe("anchor-amm-2023", () => {
         initializer
       ]).rpc();
       await confirmTx(tx);
+      console.log("Your deposit transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Set stale oracle", async () => {
+    try {
+      const tx = await program.methods.setOracle(
+        oracle_stale,
+        oracleProgram,
+        new BN(60),
+        100,
+        0
+      )
+      .accounts({
+        user: initializer.publicKey,
+        config,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
       console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Fail to swap with stale oracle", async () => {
+    try {
+      const tx = await program.methods.swap(
+        true,
+        new BN(2),
+        new BN(2),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict({
+        auth,
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        userX: initializer_x_ata,
+        userY: initializer_y_ata,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        recipient: null,
+        config,
+        observations: observationsFor(config),
+        oracle: oracle_stale,
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedRecipient: null,
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Swap should fail with a stale oracle", tx);
     } catch(e) {
       let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "StaleOracle") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Set oracle owned by another program", async () => {
+    try {
+      const tx = await program.methods.setOracle(
+        oracle_unowned,
+        oracleProgram,
+        new BN(60),
+        100,
+        0
+      )
+      .accounts({
+        user: initializer.publicKey,
+        config,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
       console.error(e);
-      if(err.error.errorCode.code !== "InvalidAuthority") {
+      throw(e)
+    }
+  });
+
+  it("Fail to swap with an oracle owned by another program", async () => {
+    try {
+      const tx = await program.methods.swap(
+        true,
+        new BN(2),
+        new BN(2),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict({
+        auth,
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        userX: initializer_x_ata,
+        userY: initializer_y_ata,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        recipient: null,
+        config,
+        observations: observationsFor(config),
+        oracle: oracle_unowned,
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedRecipient: null,
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Swap should fail with an oracle owned by another program", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "InvalidOracle") {
         throw (e)
       }
     }
   });
+
+  it("Set oracle", async () => {
+    try {
+      // The fixture was published at 2023-01-01, so allow it to be up to 20 years old
+      const tx = await program.methods.setOracle(
+        oracle,
+        oracleProgram,
+        new BN(20 * 365 * 24 * 60 * 60),
+        100,
+        0
+      )
+      .accounts({
+        user: initializer.publicKey,
+        config,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Swap X for Y with oracle", async () => {
+    try {
+      // Oracle quotes 1.5 Y per X against a 20/30 pool: 2 X -> floor(30 * 2 / 22) = 2 Y
+      const tx = await program.methods.swap(
+        true,
+        new BN(2),
+        new BN(2),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict({
+        auth,
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        userX: initializer_x_ata,
+        userY: initializer_y_ata,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        recipient: null,
+        config,
+        observations: observationsFor(config),
+        oracle,
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedRecipient: null,
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Remove oracle", async () => {
+    try {
+      const tx = await program.methods.setOracle(
+        null,
+        PublicKey.default,
+        new BN(0),
+        0,
+        0
+      )
+      .accounts({
+        user: initializer.publicKey,
+        config,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Deposit exact X", async () => {
+    try {
+      const tx = await program.methods.depositExactX(
+        new BN(10),
+        new BN(20),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict({
+        auth,
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp,
+        userX: initializer_x_ata,
+        userY: initializer_y_ata,
+        userLp: initializer_lp_ata,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        vaultLp: vault_lp_ata,
+        recipientLp: null,
+        config,
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedLp: blockedFor(config, initializer.publicKey),
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Deposit single-sided X", async () => {
+    try {
+      const tx = await program.methods.depositSingle(
+        true,
+        new BN(10),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict({
+        auth,
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp,
+        userX: initializer_x_ata,
+        userY: initializer_y_ata,
+        userLp: initializer_lp_ata,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        vaultLp: vault_lp_ata,
+        recipientLp: null,
+        config,
+        observations: observationsFor(config),
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedLp: blockedFor(config, initializer.publicKey),
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Withdraw single-sided Y", async () => {
+    try {
+      const tx = await program.methods.withdrawSingle(
+        false,
+        new BN(3),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict({
+        auth,
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp,
+        userX: initializer_x_ata,
+        userY: initializer_y_ata,
+        userLp: initializer_lp_ata,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        recipientX: null,
+        recipientY: null,
+        config,
+        observations: observationsFor(config),
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedReceiver: blockedFor(config, initializer.publicKey),
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Initialize with liquidity", async () => {
+    try {
+      const seed = new BN(randomBytes(8));
+      const config = PublicKey.findProgramAddressSync([Buffer.from("config"), seed.toBuffer().reverse()], program.programId)[0];
+      const mint_lp = PublicKey.findProgramAddressSync([Buffer.from("lp"), config.toBuffer()], program.programId)[0];
+      const [x, y] = await Promise.all([initializer, initializer].map(async(a) => { return await newMintToAta(anchor.getProvider().connection, a) }));
+      const tx = await program.methods.initializeWithLiquidity(
+        seed,
+        30,
+        initializer.publicKey,
+        new BN(1_000_000),
+        new BN(4_000_000),
+        null
+      )
+      .accountsStrict({
+        auth,
+        initializer: initializer.publicKey,
+        mintX: x.mint,
+        mintY: y.mint,
+        mintLp: mint_lp,
+        vaultX: await getAssociatedTokenAddress(x.mint, auth, true, tokenProgram),
+        vaultY: await getAssociatedTokenAddress(y.mint, auth, true, tokenProgram),
+        vaultLp: await getAssociatedTokenAddress(mint_lp, auth, true, tokenProgram),
+        initializerX: x.ata,
+        initializerY: y.ata,
+        initializerLp: await getAssociatedTokenAddress(mint_lp, initializer.publicKey, false, tokenProgram),
+        config,
+        observations: observationsFor(config),
+        lpMetadata: null,
+        tokenMetadataProgram: null,
+        rent: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Set flash fee", async () => {
+    try {
+      const tx = await program.methods.setFlashFee(
+        30
+      )
+      .accounts({
+        user: initializer.publicKey,
+        config,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  const flashAccounts = () => ({
+    auth,
+    user: initializer.publicKey,
+    mintX: mint_x,
+    mintY: mint_y,
+    userX: initializer_x_ata,
+    userY: initializer_y_ata,
+    vaultX: vault_x_ata,
+    vaultY: vault_y_ata,
+    config,
+    instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+    systemProgram: SystemProgram.programId
+  });
+
+  it("Flash loan and repay", async () => {
+    try {
+      const loan = await program.methods.flashLoan(
+        true,
+        new BN(5)
+      )
+      .accountsStrict(flashAccounts())
+      .instruction();
+      const repay = await program.methods.flashRepay()
+      .accountsStrict(flashAccounts())
+      .instruction();
+      const tx = await anchor.getProvider().sendAndConfirm(new Transaction().add(loan, repay), [initializer]);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Fail to flash loan without repay", async () => {
+    try {
+      const tx = await program.methods.flashLoan(
+        true,
+        new BN(5)
+      )
+      .accountsStrict(flashAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Flash loan should fail without a repay", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "FlashLoanNotRepaid") {
+        throw (e)
+      }
+    }
+  });
+
+  // The memo program succeeds without paying anything back into the vaults
+  const memoProgram = new PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+  const flashSwapAccounts = () => ({
+    auth,
+    user: initializer.publicKey,
+    mintX: mint_x,
+    mintY: mint_y,
+    userX: initializer_x_ata,
+    userY: initializer_y_ata,
+    vaultX: vault_x_ata,
+    vaultY: vault_y_ata,
+    config,
+    observations: observationsFor(config),
+    callbackProgram: memoProgram,
+    blocked: blockedFor(config, initializer.publicKey),
+    blockedX: blockedFor(config, initializer.publicKey),
+    blockedY: blockedFor(config, initializer.publicKey),
+    member: null,
+    gateToken: null,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+    systemProgram: SystemProgram.programId
+  });
+
+  it("Fail to flash swap without paying", async () => {
+    try {
+      const tx = await program.methods.flashSwap(
+        false,
+        new BN(1),
+        Buffer.from("flash swap")
+      )
+      .accountsStrict(flashSwapAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Flash swap should fail without payment", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "InvariantViolated") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Fail to flash swap from a blocked wallet", async () => {
+    const blocked = blockedFor(config, initializer.publicKey);
+    try {
+      await confirmTx(await program.methods.blockWallet(initializer.publicKey).accountsStrict({
+        user: initializer.publicKey,
+        blocked,
+        adminQueue: null,
+        config,
+        systemProgram: SystemProgram.programId
+      }).signers([initializer]).rpc());
+      const tx = await program.methods.flashSwap(
+        false,
+        new BN(1),
+        Buffer.from("flash swap")
+      )
+      .accountsStrict(flashSwapAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Flash swap should fail from a blocked wallet", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "WalletBlocked") {
+        throw (e)
+      }
+    }
+    await confirmTx(await program.methods.unblockWallet().accountsStrict({
+      user: initializer.publicKey,
+      blocked,
+      adminQueue: null,
+      config
+    }).signers([initializer]).rpc());
+  });
+
+  it("Swap X for Y to recipient", async () => {
+    try {
+      // Non-ATA token account owned by another wallet
+      const recipient = await createAccount(anchor.getProvider().connection, initializer, mint_y, user.publicKey, Keypair.generate());
+      const tx = await program.methods.swap(
+        true,
+        new BN(2),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict({
+        auth,
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        userX: initializer_x_ata,
+        userY: initializer_y_ata,
+        recipient,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        config,
+        observations: observationsFor(config),
+        oracle: null,
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedRecipient: blockedFor(config, user.publicKey),
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Swap X for Y from non-ATA accounts", async () => {
+    try {
+      const connection = anchor.getProvider().connection;
+      const [user_x, user_y] = await Promise.all([mint_x, mint_y].map(async(m) => {
+        return await createAccount(connection, initializer, m, initializer.publicKey, Keypair.generate())
+      }));
+      await confirmTx(await mintTo(connection, initializer, mint_x, user_x, initializer, 10));
+      const tx = await program.methods.swap(
+        true,
+        new BN(2),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict({
+        auth,
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        userX: user_x,
+        userY: user_y,
+        recipient: null,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        config,
+        observations: observationsFor(config),
+        oracle: null,
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedRecipient: null,
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Swap native SOL for Y with wrap", async () => {
+    try {
+      const connection = anchor.getProvider().connection;
+      const seed = new BN(randomBytes(8));
+      const config = PublicKey.findProgramAddressSync([Buffer.from("config"), seed.toBuffer().reverse()], program.programId)[0];
+      const mint_lp = PublicKey.findProgramAddressSync([Buffer.from("lp"), config.toBuffer()], program.programId)[0];
+      const y = await newMintToAta(connection, initializer);
+      const user_x = await createWrappedNativeAccount(connection, initializer, initializer.publicKey, 1_000_000);
+      const vault_x = await getAssociatedTokenAddress(NATIVE_MINT, auth, true, tokenProgram);
+      const vault_y = await getAssociatedTokenAddress(y.mint, auth, true, tokenProgram);
+      await confirmTx(await program.methods.initializeWithLiquidity(
+        seed,
+        30,
+        initializer.publicKey,
+        new BN(1_000_000),
+        new BN(4_000_000),
+        null
+      )
+      .accountsStrict({
+        auth,
+        initializer: initializer.publicKey,
+        mintX: NATIVE_MINT,
+        mintY: y.mint,
+        mintLp: mint_lp,
+        vaultX: vault_x,
+        vaultY: vault_y,
+        vaultLp: await getAssociatedTokenAddress(mint_lp, auth, true, tokenProgram),
+        initializerX: user_x,
+        initializerY: y.ata,
+        initializerLp: await getAssociatedTokenAddress(mint_lp, initializer.publicKey, false, tokenProgram),
+        config,
+        observations: observationsFor(config),
+        lpMetadata: null,
+        tokenMetadataProgram: null,
+        rent: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc());
+      const accounts = {
+        auth,
+        user: initializer.publicKey,
+        mintX: NATIVE_MINT,
+        mintY: y.mint,
+        userX: user_x,
+        userY: y.ata,
+        recipient: null,
+        vaultX: vault_x,
+        vaultY: vault_y,
+        config,
+        observations: observationsFor(config),
+        oracle: null,
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedRecipient: null,
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      };
+      // Without a wSOL ATA the swap creates one, tops it up with lamports and closes it again
+      await confirmTx(await closeAccount(connection, initializer, user_x, initializer.publicKey, initializer));
+      const tx = await program.methods.swap(
+        true,
+        new BN(10_000),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        true
+      )
+      .accountsStrict(accounts)
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      assert.isNull(await connection.getAccountInfo(user_x, commitment), "wSOL account should be closed");
+
+      // A wSOL ATA that already exists keeps its balance and receives the output as wSOL
+      await createWrappedNativeAccount(connection, initializer, initializer.publicKey, 5_000);
+      await confirmTx(await program.methods.swap(
+        false,
+        new BN(10_000),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        true
+      )
+      .accountsStrict(accounts)
+      .signers([
+        initializer
+      ]).rpc());
+      assert.isTrue((await getAccount(connection, user_x, commitment)).amount > BigInt(5_000));
+
+      // Depositing with wrap also creates the missing wSOL ATA and closes it afterwards
+      await confirmTx(await closeAccount(connection, initializer, user_x, initializer.publicKey, initializer));
+      await confirmTx(await program.methods.deposit(
+        new BN(1_000),
+        new BN(1_000_000),
+        new BN(1_000_000),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        true
+      )
+      .accountsStrict({
+        auth,
+        user: initializer.publicKey,
+        mintX: NATIVE_MINT,
+        mintY: y.mint,
+        mintLp: mint_lp,
+        userX: user_x,
+        userY: y.ata,
+        userLp: await getAssociatedTokenAddress(mint_lp, initializer.publicKey, false, tokenProgram),
+        vaultX: vault_x,
+        vaultY: vault_y,
+        vaultLp: await getAssociatedTokenAddress(mint_lp, auth, true, tokenProgram),
+        recipientLp: null,
+        config,
+        observations: observationsFor(config),
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedX: blockedFor(config, initializer.publicKey),
+        blockedY: blockedFor(config, initializer.publicKey),
+        blockedLp: blockedFor(config, initializer.publicKey),
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc());
+      assert.isNull(await connection.getAccountInfo(user_x, commitment), "wSOL account should be closed");
+
+      // So does a single-sided withdrawal paid out in SOL
+      await confirmTx(await program.methods.withdrawSingle(
+        true,
+        new BN(1_000),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        true
+      )
+      .accountsStrict({
+        auth,
+        user: initializer.publicKey,
+        mintX: NATIVE_MINT,
+        mintY: y.mint,
+        mintLp: mint_lp,
+        userX: user_x,
+        userY: y.ata,
+        userLp: await getAssociatedTokenAddress(mint_lp, initializer.publicKey, false, tokenProgram),
+        vaultX: vault_x,
+        vaultY: vault_y,
+        recipientX: null,
+        recipientY: null,
+        config,
+        observations: observationsFor(config),
+        blocked: blockedFor(config, initializer.publicKey),
+        blockedReceiver: blockedFor(config, initializer.publicKey),
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc());
+      assert.isNull(await connection.getAccountInfo(user_x, commitment), "wSOL account should be closed");
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Increase observation cardinality", async () => {
+    try {
+      // Anyone can pay to store more observations
+      const tx = await program.methods.increaseObservationCardinality(
+        8
+      )
+      .accountsStrict({
+        payer: user.publicKey,
+        config,
+        observations: observationsFor(config),
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        user
+      ]).rpc();
+      await confirmTx(tx);
+      const observations = await program.account.observations.fetch(observationsFor(config));
+      assert.equal(observations.cardinality, 8);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Observe TWAP", async () => {
+    const accounts = {
+      config,
+      observations: observationsFor(config)
+    };
+    const twap = await program.methods.observe(1).accountsStrict(accounts).view();
+    assert.isTrue(twap.priceX.gtn(0) && twap.priceY.gtn(0) && twap.liquidity.gtn(0));
+    try {
+      await program.methods.observe(0).accountsStrict(accounts).view();
+      assert.fail("Observing an empty window should fail");
+    } catch(e) {
+      if(!e.toString().includes("InvalidAmount")) {
+        throw (e)
+      }
+    }
+    try {
+      await program.methods.observe(3600).accountsStrict(accounts).view();
+      assert.fail("Observation before the oldest stored one should fail");
+    } catch(e) {
+      if(!e.toString().includes("ObservationTooOld")) {
+        throw (e)
+      }
+    }
+  });
+
+  const swapAccounts = () => ({
+    auth,
+    user: initializer.publicKey,
+    mintX: mint_x,
+    mintY: mint_y,
+    userX: initializer_x_ata,
+    userY: initializer_y_ata,
+    recipient: null,
+    vaultX: vault_x_ata,
+    vaultY: vault_y_ata,
+    config,
+    observations: observationsFor(config),
+    oracle: null,
+    blocked: blockedFor(config, initializer.publicKey),
+    blockedX: blockedFor(config, initializer.publicKey),
+    blockedY: blockedFor(config, initializer.publicKey),
+    blockedRecipient: null,
+    member: null,
+    gateToken: null,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+    systemProgram: SystemProgram.programId
+  });
+
+  it("Fail to swap past the price band", async () => {
+    const accounts = {
+      user: initializer.publicKey,
+      config,
+      systemProgram: SystemProgram.programId
+    };
+    try {
+      await confirmTx(await program.methods.setCircuitBreaker(1, 3600, false).accounts(accounts).signers([initializer]).rpc());
+      const tx = await program.methods.swap(
+        true,
+        new BN(2),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(swapAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Swap should fail past the price band", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "CircuitBreaker") {
+        throw (e)
+      }
+    }
+    assert.isFalse((await program.account.config.fetch(config)).swapsPaused);
+  });
+
+  it("Set circuit breaker", async () => {
+    try {
+      const tx = await program.methods.setCircuitBreaker(
+        1000,
+        3600,
+        true
+      )
+      .accounts({
+        user: initializer.publicKey,
+        config,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  const tripAccounts = () => ({
+    config
+  });
+
+  it("Fail to swap past the price band with pause on break", async () => {
+    try {
+      const vault_x = (await getAccount(anchor.getProvider().connection, vault_x_ata)).amount;
+      const tx = await program.methods.swap(
+        true,
+        new BN((vault_x / BigInt(5)).toString()),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(swapAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Swap should fail past the price band", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "CircuitBreaker") {
+        throw (e)
+      }
+    }
+    // The breaching trade is rejected, so it can't pause swaps either
+    assert.isFalse((await program.account.config.fetch(config)).swapsPaused);
+  });
+
+  it("Swap within the price band", async () => {
+    try {
+      const vault_x = (await getAccount(anchor.getProvider().connection, vault_x_ata)).amount;
+      const tx = await program.methods.swap(
+        true,
+        new BN((vault_x / BigInt(1000)).toString()),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(swapAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Fail to trip circuit breaker within the band", async () => {
+    try {
+      const tx = await program.methods.tripCircuitBreaker()
+      .accountsStrict(tripAccounts())
+      .rpc();
+      assert.fail("Tripping should fail within the band", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "PriceInBand") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Fail to trip circuit breaker on unrecorded vault balances", async () => {
+    try {
+      // Transfers straight into a vault move the price without going through the band check
+      const vault_x = (await getAccount(anchor.getProvider().connection, vault_x_ata)).amount;
+      await confirmTx(await transfer(anchor.getProvider().connection, initializer, initializer_x_ata, vault_x_ata, initializer, vault_x / BigInt(5)));
+      const tx = await program.methods.tripCircuitBreaker()
+      .accountsStrict(tripAccounts())
+      .rpc();
+      assert.fail("Tripping should only use recorded reserves", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "PriceInBand") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Trip circuit breaker", async () => {
+    try {
+      // A plain deposit records the reserves the transfer moved
+      const connection = anchor.getProvider().connection;
+      const [vault_x, vault_y] = await Promise.all([vault_x_ata, vault_y_ata].map(async (v) => (await getAccount(connection, v)).amount));
+      await confirmTx(await program.methods.deposit(
+        new BN(1_000),
+        new BN(vault_x.toString()),
+        new BN(vault_y.toString()),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(depositAccounts())
+      .signers([
+        initializer
+      ]).rpc());
+      const tx = await program.methods.tripCircuitBreaker()
+      .accountsStrict(tripAccounts())
+      .rpc();
+      await confirmTx(tx);
+      assert.isTrue((await program.account.config.fetch(config)).swapsPaused);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Fail to swap while paused", async () => {
+    try {
+      const tx = await program.methods.swap(
+        true,
+        new BN(2),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(swapAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Swap should fail while paused", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "SwapsPaused") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Fail to deposit single-sided while paused", async () => {
+    try {
+      const tx = await program.methods.depositSingle(
+        true,
+        new BN(2),
+        new BN(0),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(depositAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Single-sided deposit should fail while paused", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "SwapsPaused") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Unlock and disable circuit breaker", async () => {
+    try {
+      const accounts = {
+        user: initializer.publicKey,
+        config,
+        systemProgram: SystemProgram.programId
+      };
+      await confirmTx(await program.methods.unlock().accounts(accounts).signers([initializer]).rpc());
+      const tx = await program.methods.setCircuitBreaker(0, 0, false).accounts(accounts).signers([initializer]).rpc();
+      await confirmTx(tx);
+      assert.isFalse((await program.account.config.fetch(config)).swapsPaused);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Fail to swap over the outflow limit", async () => {
+    const accounts = {
+      user: initializer.publicKey,
+      config,
+      systemProgram: SystemProgram.programId
+    };
+    try {
+      await confirmTx(await program.methods.setOutflowLimit(1, new BN(100)).accounts(accounts).signers([initializer]).rpc());
+      const tx = await program.methods.swap(
+        true,
+        new BN(2),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(swapAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Swap should fail over the outflow limit", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "OutflowLimitExceeded") {
+        throw (e)
+      }
+    }
+    await confirmTx(await program.methods.setOutflowLimit(0, new BN(0)).accounts(accounts).signers([initializer]).rpc());
+  });
+
+  const depositAccounts = () => ({
+    auth,
+    user: initializer.publicKey,
+    mintX: mint_x,
+    mintY: mint_y,
+    mintLp: mint_lp,
+    userX: initializer_x_ata,
+    userY: initializer_y_ata,
+    userLp: initializer_lp_ata,
+    vaultX: vault_x_ata,
+    vaultY: vault_y_ata,
+    vaultLp: vault_lp_ata,
+    recipientLp: null,
+    config,
+    observations: observationsFor(config),
+    blocked: blockedFor(config, initializer.publicKey),
+    blockedX: blockedFor(config, initializer.publicKey),
+    blockedY: blockedFor(config, initializer.publicKey),
+    blockedLp: blockedFor(config, initializer.publicKey),
+    member: null,
+    gateToken: null,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+    systemProgram: SystemProgram.programId
+  });
+
+  it("Fail to deposit over the deposit cap", async () => {
+    const accounts = {
+      user: initializer.publicKey,
+      config,
+      systemProgram: SystemProgram.programId
+    };
+    try {
+      await confirmTx(await program.methods.setDepositCap(new BN(1), new BN(1)).accounts(accounts).signers([initializer]).rpc());
+      const tx = await program.methods.deposit(
+        new BN(20),
+        new BN(20),
+        new BN(30),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(depositAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Deposit should fail over the deposit cap", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "DepositCapExceeded") {
+        throw (e)
+      }
+    }
+    await confirmTx(await program.methods.setDepositCap(new BN(0), new BN(0)).accounts(accounts).signers([initializer]).rpc());
+  });
+
+  const member = PublicKey.findProgramAddressSync([Buffer.from("member"), config.toBuffer(), initializer.publicKey.toBuffer()], program.programId)[0];
+
+  it("Fail to deposit into an LP-gated pool without membership", async () => {
+    try {
+      await confirmTx(await program.methods.setAccessMode({ lpGated: {} }, null).accounts({
+        user: initializer.publicKey,
+        config,
+        systemProgram: SystemProgram.programId
+      }).signers([initializer]).rpc());
+      const tx = await program.methods.deposit(
+        new BN(20),
+        new BN(20),
+        new BN(30),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(depositAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Deposit should fail without membership", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "NotAMember") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Deposit into an LP-gated pool as a member", async () => {
+    try {
+      await confirmTx(await program.methods.addMember(initializer.publicKey).accountsStrict({
+        user: initializer.publicKey,
+        member,
+        adminQueue: null,
+        config,
+        systemProgram: SystemProgram.programId
+      }).signers([initializer]).rpc());
+      const tx = await program.methods.deposit(
+        new BN(20),
+        new BN(20),
+        new BN(30),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict({ ...depositAccounts(), member })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      await confirmTx(await program.methods.removeMember().accountsStrict({
+        user: initializer.publicKey,
+        member,
+        adminQueue: null,
+        config
+      }).signers([initializer]).rpc());
+      await confirmTx(await program.methods.setAccessMode({ open: {} }, null).accounts({
+        user: initializer.publicKey,
+        config,
+        systemProgram: SystemProgram.programId
+      }).signers([initializer]).rpc());
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Fail to swap from a blocked wallet", async () => {
+    const blocked = blockedFor(config, initializer.publicKey);
+    try {
+      await confirmTx(await program.methods.setRoles(null, null, initializer.publicKey).accounts({
+        user: initializer.publicKey,
+        config,
+        systemProgram: SystemProgram.programId
+      }).signers([initializer]).rpc());
+      await confirmTx(await program.methods.blockWallet(initializer.publicKey).accountsStrict({
+        user: initializer.publicKey,
+        blocked,
+        adminQueue: null,
+        config,
+        systemProgram: SystemProgram.programId
+      }).signers([initializer]).rpc());
+      const tx = await program.methods.swap(
+        true,
+        new BN(2),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(swapAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Swap should fail from a blocked wallet", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "WalletBlocked") {
+        throw (e)
+      }
+    }
+    await confirmTx(await program.methods.unblockWallet().accountsStrict({
+      user: initializer.publicKey,
+      blocked,
+      adminQueue: null,
+      config
+    }).signers([initializer]).rpc());
+  });
+
+  it("Fail to swap to a blocked recipient", async () => {
+    const blocked = blockedFor(config, user.publicKey);
+    try {
+      await confirmTx(await program.methods.blockWallet(user.publicKey).accountsStrict({
+        user: initializer.publicKey,
+        blocked,
+        adminQueue: null,
+        config,
+        systemProgram: SystemProgram.programId
+      }).signers([initializer]).rpc());
+      const recipient = await createAccount(anchor.getProvider().connection, initializer, mint_y, user.publicKey, Keypair.generate());
+      const tx = await program.methods.swap(
+        true,
+        new BN(2),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict({ ...swapAccounts(), recipient, blockedRecipient: blocked })
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Swap should fail to a blocked recipient", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "WalletBlocked") {
+        throw (e)
+      }
+    }
+    await confirmTx(await program.methods.unblockWallet().accountsStrict({
+      user: initializer.publicKey,
+      blocked,
+      adminQueue: null,
+      config
+    }).signers([initializer]).rpc());
+  });
+
+  it("Guardian can pause but not unpause", async () => {
+    const accounts = (signer: Keypair) => ({
+      user: signer.publicKey,
+      config,
+      systemProgram: SystemProgram.programId
+    });
+    await confirmTx(await program.methods.setRoles(user.publicKey, null, null).accounts(accounts(initializer)).signers([initializer]).rpc());
+    await confirmTx(await program.methods.lock().accounts(accounts(user)).signers([user]).rpc());
+    try {
+      const tx = await program.methods.unlock().accounts(accounts(user)).signers([user]).rpc();
+      assert.fail("Guardian should not be able to unlock", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "InvalidAuthority") {
+        throw (e)
+      }
+    }
+    await confirmTx(await program.methods.unlock().accounts(accounts(initializer)).signers([initializer]).rpc());
+    await confirmTx(await program.methods.setRoles(null, null, null).accounts(accounts(initializer)).signers([initializer]).rpc());
+  });
+
+  const admin_queue = PublicKey.findProgramAddressSync([Buffer.from("admin_queue"), config.toBuffer()], program.programId)[0];
+  const encodeAction = (action: object) => program.coder.types.encode("AdminAction", action);
+
+  it("Queue, cancel and execute admin actions", async () => {
+    try {
+      const queueAccounts = {
+        user: initializer.publicKey,
+        adminQueue: admin_queue,
+        config,
+        systemProgram: SystemProgram.programId
+      };
+      const actionAccounts = { user: initializer.publicKey, adminQueue: admin_queue, config };
+      const member = PublicKey.findProgramAddressSync([Buffer.from("member"), config.toBuffer(), user.publicKey.toBuffer()], program.programId)[0];
+      // Without a timelock queued actions are ready as soon as they're queued
+      await confirmTx(await program.methods.queueAction(encodeAction({ setFlashFee: { flashFee: 40 } })).accountsStrict(queueAccounts).signers([initializer]).rpc());
+      await confirmTx(await program.methods.queueAction(encodeAction({ setFee: { fee: 5000 } })).accountsStrict(queueAccounts).signers([initializer]).rpc());
+      await confirmTx(await program.methods.queueAction(encodeAction({ addMember: { wallet: user.publicKey } })).accountsStrict(queueAccounts).signers([initializer]).rpc());
+      await confirmTx(await program.methods.cancelAction(new BN(1)).accountsStrict(actionAccounts).signers([initializer]).rpc());
+      await confirmTx(await program.methods.executeAction(new BN(0)).accountsStrict(actionAccounts).signers([initializer]).rpc());
+      // Actions that need their own accounts are executed by their instruction, which takes them off the queue
+      await confirmTx(await program.methods.addMember(user.publicKey).accountsStrict({
+        user: initializer.publicKey,
+        member,
+        adminQueue: admin_queue,
+        config,
+        systemProgram: SystemProgram.programId
+      }).signers([initializer]).rpc());
+      const tx = await program.methods.removeMember().accountsStrict({
+        user: initializer.publicKey,
+        member,
+        adminQueue: null,
+        config
+      }).signers([initializer]).rpc();
+      await confirmTx(tx);
+
+      const state = await program.account.config.fetch(config);
+      assert.equal(state.flashFee, 40);
+      assert.equal(state.fee, 0);
+      assert.isEmpty((await program.account.adminQueue.fetch(admin_queue)).actions);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  // A separate pool keeps its timelock, so nothing has to wait for an ETA to reset it
+  const timelock_seed = new BN(randomBytes(8));
+  const timelock_config = PublicKey.findProgramAddressSync([Buffer.from("config"), timelock_seed.toBuffer().reverse()], program.programId)[0];
+  const timelock_mint_lp = PublicKey.findProgramAddressSync([Buffer.from("lp"), timelock_config.toBuffer()], program.programId)[0];
+  const timelock_queue = PublicKey.findProgramAddressSync([Buffer.from("admin_queue"), timelock_config.toBuffer()], program.programId)[0];
+  const timelock_member = PublicKey.findProgramAddressSync([Buffer.from("member"), timelock_config.toBuffer(), user.publicKey.toBuffer()], program.programId)[0];
+  const timelockAccounts = {
+    user: initializer.publicKey,
+    config: timelock_config,
+    systemProgram: SystemProgram.programId
+  };
+  const timelockQueueAccounts = { ...timelockAccounts, adminQueue: timelock_queue };
+  const timelockActionAccounts = { user: initializer.publicKey, adminQueue: timelock_queue, config: timelock_config };
+  const timelockMemberAccounts = (adminQueue: PublicKey | null) => ({
+    user: initializer.publicKey,
+    member: timelock_member,
+    adminQueue,
+    config: timelock_config,
+    systemProgram: SystemProgram.programId
+  });
+
+  it("Initialize a pool with a timelock", async () => {
+    try {
+      const connection = anchor.getProvider().connection;
+      const [x, y] = await Promise.all([newMintToAta(connection, initializer), newMintToAta(connection, initializer)]);
+      await confirmTx(await program.methods.initialize(
+        timelock_seed,
+        0,
+        initializer.publicKey,
+        null
+      )
+      .accountsStrict({
+        auth,
+        initializer: initializer.publicKey,
+        mintX: x.mint,
+        mintY: y.mint,
+        mintLp: timelock_mint_lp,
+        vaultX: await getAssociatedTokenAddress(x.mint, auth, true, tokenProgram),
+        vaultY: await getAssociatedTokenAddress(y.mint, auth, true, tokenProgram),
+        vaultLp: await getAssociatedTokenAddress(timelock_mint_lp, auth, true, tokenProgram),
+        config: timelock_config,
+        observations: observationsFor(timelock_config),
+        lpMetadata: null,
+        tokenMetadataProgram: null,
+        rent: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc());
+      const tx = await program.methods.setTimelockDelay(new BN(24 * 60 * 60)).accounts(timelockAccounts).signers([initializer]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Fail to change settings directly behind a timelock", async () => {
+    try {
+      const tx = await program.methods.setFlashFee(40).accounts(timelockAccounts).signers([initializer]).rpc();
+      assert.fail("Flash fee change should need the timelock", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "TimelockActive") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Fail to add a member directly behind a timelock", async () => {
+    try {
+      const tx = await program.methods.addMember(user.publicKey).accountsStrict(timelockMemberAccounts(null)).signers([initializer]).rpc();
+      assert.fail("Adding a member should need the timelock", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "TimelockActive") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Queue and cancel admin actions behind a timelock", async () => {
+    try {
+      await confirmTx(await program.methods.queueAction(encodeAction({ setFlashFee: { flashFee: 40 } })).accountsStrict(timelockQueueAccounts).signers([initializer]).rpc());
+      await confirmTx(await program.methods.queueAction(encodeAction({ setFee: { fee: 5000 } })).accountsStrict(timelockQueueAccounts).signers([initializer]).rpc());
+      await confirmTx(await program.methods.queueAction(encodeAction({ addMember: { wallet: user.publicKey } })).accountsStrict(timelockQueueAccounts).signers([initializer]).rpc());
+      const tx = await program.methods.cancelAction(new BN(1)).accountsStrict(timelockActionAccounts).signers([initializer]).rpc();
+      await confirmTx(tx);
+      assert.deepEqual((await program.account.adminQueue.fetch(timelock_queue)).actions.map((a) => a.id.toNumber()), [0, 2]);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Fail to execute an admin action before its ETA", async () => {
+    try {
+      const tx = await program.methods.executeAction(new BN(0)).accountsStrict(timelockActionAccounts).signers([initializer]).rpc();
+      assert.fail("Action should not execute before its ETA", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "ActionNotReady") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Fail to add a queued member before its ETA", async () => {
+    try {
+      const tx = await program.methods.addMember(user.publicKey).accountsStrict(timelockMemberAccounts(timelock_queue)).signers([initializer]).rpc();
+      assert.fail("Queued member should not be added before its ETA", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "ActionNotReady") {
+        throw (e)
+      }
+    }
+  });
+
+  // LP farming
+  const farm = PublicKey.findProgramAddressSync([Buffer.from("farm"), config.toBuffer()], program.programId)[0];
+  const stake_info = PublicKey.findProgramAddressSync([Buffer.from("stake"), farm.toBuffer(), initializer.publicKey.toBuffer()], program.programId)[0];
+  let reward_mints: PublicKey[] = [];
+  let farm_lp: PublicKey;
+
+  const stakeAccounts = () => ({
+    user: initializer.publicKey,
+    mintLp: mint_lp,
+    farm,
+    stakeInfo: stake_info,
+    userLp: initializer_lp_ata,
+    farmLp: farm_lp,
+    config,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+    systemProgram: SystemProgram.programId
+  });
+  // Mint, farm vault and user account for each reward stream
+  const rewardAccounts = () => reward_mints.flatMap((mint) => [
+    mint,
+    getAssociatedTokenAddressSync(mint, farm, true),
+    getAssociatedTokenAddressSync(mint, initializer.publicKey)
+  ]).map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+  const fundRewardStream = async (reward_mint: PublicKey, amount: number) => {
+    const connection = anchor.getProvider().connection;
+    const source = await createAccount(connection, initializer, reward_mint, initializer.publicKey, Keypair.generate());
+    await confirmTx(await mintTo(connection, initializer, reward_mint, source, initializer, amount));
+    await confirmTx(await program.methods.fundRewardStream(
+      new BN(amount)
+    )
+    .accountsStrict({
+      user: initializer.publicKey,
+      rewardMint: reward_mint,
+      farm,
+      userReward: source,
+      rewardVault: getAssociatedTokenAddressSync(reward_mint, farm, true),
+      tokenProgram: TOKEN_PROGRAM_ID
+    })
+    .signers([
+      initializer
+    ]).rpc());
+  };
+
+  it("Initialize farm", async () => {
+    try {
+      const connection = anchor.getProvider().connection;
+      const reward_mint = await createMint(connection, initializer, initializer.publicKey, null, 6);
+      reward_mints.push(reward_mint);
+      farm_lp = getAssociatedTokenAddressSync(mint_lp, farm, true);
+      const now = Math.floor(new Date().getTime()/1000);
+      const tx = await program.methods.initializeFarm(
+        new BN(1000),
+        new BN(now - 60),
+        new BN(now + 24 * 60 * 60)
+      )
+      .accountsStrict({
+        user: initializer.publicKey,
+        mintLp: mint_lp,
+        rewardMint: reward_mint,
+        farm,
+        farmLp: farm_lp,
+        rewardVault: getAssociatedTokenAddressSync(reward_mint, farm, true),
+        adminQueue: null,
+        config,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      await fundRewardStream(reward_mint, 1_000_000_000);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Stake LP", async () => {
+    try {
+      const tx = await program.methods.stakeLp(
+        new BN(1)
+      )
+      .accountsStrict(stakeAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Add a second reward stream", async () => {
+    try {
+      const connection = anchor.getProvider().connection;
+      const reward_mint = await createMint(connection, initializer, initializer.publicKey, null, 6);
+      const now = Math.floor(new Date().getTime()/1000);
+      const tx = await program.methods.addRewardStream(
+        new BN(500),
+        new BN(now - 60),
+        new BN(now + 24 * 60 * 60)
+      )
+      .accountsStrict({
+        user: initializer.publicKey,
+        rewardMint: reward_mint,
+        farm,
+        rewardVault: getAssociatedTokenAddressSync(reward_mint, farm, true),
+        adminQueue: null,
+        config,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      reward_mints.push(reward_mint);
+      await fundRewardStream(reward_mint, 1_000_000_000);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Fail to add a reward stream that ends before it starts", async () => {
+    try {
+      const connection = anchor.getProvider().connection;
+      const reward_mint = await createMint(connection, initializer, initializer.publicKey, null, 6);
+      const now = Math.floor(new Date().getTime()/1000);
+      const tx = await program.methods.addRewardStream(
+        new BN(500),
+        new BN(now),
+        new BN(now)
+      )
+      .accountsStrict({
+        user: initializer.publicKey,
+        rewardMint: reward_mint,
+        farm,
+        rewardVault: getAssociatedTokenAddressSync(reward_mint, farm, true),
+        adminQueue: null,
+        config,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("A stream should end after it starts", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "InvalidAmount") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Set reward rate", async () => {
+    try {
+      const tx = await program.methods.setRewardRate(
+        1,
+        new BN(2000)
+      )
+      .accountsStrict({
+        user: initializer.publicKey,
+        farm,
+        adminQueue: null,
+        config
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Claim rewards", async () => {
+    try {
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+      const tx = await program.methods.claimRewards()
+      .accountsStrict(stakeAccounts())
+      .remainingAccounts(rewardAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      // Every stream pays out
+      for (const mint of reward_mints) {
+        const balance = await anchor.getProvider().connection.getTokenAccountBalance(getAssociatedTokenAddressSync(mint, initializer.publicKey));
+        assert.isTrue(Number(balance.value.amount) > 0);
+      }
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Unstake LP", async () => {
+    try {
+      const tx = await program.methods.unstakeLp(
+        new BN(1)
+      )
+      .accountsStrict(stakeAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  // LP locks
+  const lp_lock = PublicKey.findProgramAddressSync([Buffer.from("lock"), config.toBuffer(), initializer.publicKey.toBuffer()], program.programId)[0];
+  const lockAccounts = () => ({
+    user: initializer.publicKey,
+    mintX: mint_x,
+    mintY: mint_y,
+    mintLp: mint_lp,
+    vaultX: vault_x_ata,
+    vaultY: vault_y_ata,
+    userX: initializer_x_ata,
+    userY: initializer_y_ata,
+    userLp: initializer_lp_ata,
+    lpLock: lp_lock,
+    lockVault: getAssociatedTokenAddressSync(mint_lp, lp_lock, true),
+    auth,
+    config,
+    observations: observationsFor(config),
+    tokenProgram: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+    systemProgram: SystemProgram.programId
+  });
+
+  it("Lock LP", async () => {
+    try {
+      const tx = await program.methods.lockLp(
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 3600)
+      )
+      .accountsStrict(lockAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Fail to lock LP inside a flash loan", async () => {
+    try {
+      const loan = await program.methods.flashLoan(
+        true,
+        new BN(5)
+      )
+      .accountsStrict(flashAccounts())
+      .instruction();
+      const lock = await program.methods.lockLp(
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 3600)
+      )
+      .accountsStrict(lockAccounts())
+      .instruction();
+      const repay = await program.methods.flashRepay()
+      .accountsStrict(flashAccounts())
+      .instruction();
+      const tx = await anchor.getProvider().sendAndConfirm(new Transaction().add(loan, lock, repay), [initializer]);
+      assert.fail("Lock should fail inside a flash loan", tx);
+    } catch(e) {
+      let err = anchor.translateError(e, anchor.parseIdlErrors(program.idl)) as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "FlashLoanActive") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Fail to unlock LP early", async () => {
+    try {
+      const tx = await program.methods.unlockLp()
+      .accountsStrict(lockAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Unlock should fail before unlock time", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "StillLocked") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Fail to claim lock fees before any are earned", async () => {
+    try {
+      const tx = await program.methods.claimLockFees(
+        new BN(0),
+        new BN(0)
+      )
+      .accountsStrict(lockAccounts())
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Claim should fail without fees", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "ZeroBalance") {
+        throw (e)
+      }
+    }
+  });
+
+  // Position NFTs
+  const nft_mint = Keypair.generate();
+  const position = PublicKey.findProgramAddressSync([Buffer.from("position"), nft_mint.publicKey.toBuffer()], program.programId)[0];
+  const positionAccounts = (nftMint: Keypair) => {
+    const position = PublicKey.findProgramAddressSync([Buffer.from("position"), nftMint.publicKey.toBuffer()], program.programId)[0];
+    return {
+      user: initializer.publicKey,
+      mintX: mint_x,
+      mintY: mint_y,
+      mintLp: mint_lp,
+      vaultX: vault_x_ata,
+      vaultY: vault_y_ata,
+      vaultLp: vault_lp_ata,
+      userX: initializer_x_ata,
+      userY: initializer_y_ata,
+      nftMint: nftMint.publicKey,
+      userNft: getAssociatedTokenAddressSync(nftMint.publicKey, initializer.publicKey),
+      position,
+      positionLp: getAssociatedTokenAddressSync(mint_lp, position, true),
+      auth,
+      config,
+      observations: observationsFor(config),
+      blocked: blockedFor(config, initializer.publicKey),
+      blockedX: blockedFor(config, initializer.publicKey),
+      blockedY: blockedFor(config, initializer.publicKey),
+      member: null,
+      gateToken: null,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+      systemProgram: SystemProgram.programId
+    }
+  };
+
+  it("Fail to deposit into a position from a blocked wallet", async () => {
+    const blocked = blockedFor(config, initializer.publicKey);
+    const blocked_nft_mint = Keypair.generate();
+    try {
+      await confirmTx(await program.methods.blockWallet(initializer.publicKey).accountsStrict({
+        user: initializer.publicKey,
+        blocked,
+        adminQueue: null,
+        config,
+        systemProgram: SystemProgram.programId
+      }).signers([initializer]).rpc());
+      const tx = await program.methods.depositPosition(
+        new BN(2),
+        new BN(1_000_000),
+        new BN(1_000_000),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(positionAccounts(blocked_nft_mint))
+      .signers([
+        initializer,
+        blocked_nft_mint
+      ]).rpc();
+      assert.fail("Position deposit should fail from a blocked wallet", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "WalletBlocked") {
+        throw (e)
+      }
+    }
+    await confirmTx(await program.methods.unblockWallet().accountsStrict({
+      user: initializer.publicKey,
+      blocked,
+      adminQueue: null,
+      config
+    }).signers([initializer]).rpc());
+  });
+
+  it("Deposit into a position", async () => {
+    try {
+      const tx = await program.methods.depositPosition(
+        new BN(2),
+        new BN(1_000_000),
+        new BN(1_000_000),
+        new BN(Math.floor(new Date().getTime()/1000) + 600),
+        false
+      )
+      .accountsStrict(positionAccounts(nft_mint))
+      .signers([
+        initializer,
+        nft_mint
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Donate LP to a position", async () => {
+    try {
+      // Extra LP in the position's account must not stop it from being withdrawn and closed
+      const connection = anchor.getProvider().connection;
+      const tx = await transfer(connection, initializer, initializer_lp_ata, getAssociatedTokenAddressSync(mint_lp, position, true), initializer, 1);
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Withdraw a position", async () => {
+    try {
+      const tx = await program.methods.withdrawPosition(
+        new BN(0),
+        new BN(0),
+        new BN(Math.floor(new Date().getTime()/1000) + 600)
+      )
+      .accountsStrict({
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        mintLp: mint_lp,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        userX: initializer_x_ata,
+        userY: initializer_y_ata,
+        nftMint: nft_mint.publicKey,
+        userNft: getAssociatedTokenAddressSync(nft_mint.publicKey, initializer.publicKey),
+        position,
+        positionLp: getAssociatedTokenAddressSync(mint_lp, position, true),
+        auth,
+        config,
+        observations: observationsFor(config),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      const { meta } = await anchor.getProvider().connection.getTransaction(tx, { commitment, maxSupportedTransactionVersion: 0 });
+      const events = [...new anchor.EventParser(program.programId, program.coder).parseLogs(meta.logMessages)];
+      assert.isTrue(events.some((e) => e.name === "PositionClosed"));
+      assert.isNull(await anchor.getProvider().connection.getAccountInfo(position, commitment));
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  // Multi-asset pool
+  const multi_seed = new BN(randomBytes(8));
+  const multi_pool = PublicKey.findProgramAddressSync([Buffer.from("multi"), multi_seed.toBuffer().reverse()], program.programId)[0];
+  const multi_mint_lp = PublicKey.findProgramAddressSync([Buffer.from("multi_lp"), multi_pool.toBuffer()], program.programId)[0];
+  let multi_user_atas: PublicKey[];
+  let multi_vaults: PublicKey[];
+  let multi_user_lp: PublicKey;
+  let multi_pool_lp: PublicKey;
+
+  const multiAccounts = (keys: PublicKey[]) => keys.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+
+  it("Initialize multi-asset pool", async () => {
+    try {
+      const mints = await Promise.all([initializer, initializer, initializer].map(async(a) => { return await newMintToAta(anchor.getProvider().connection, a) }));
+      multi_user_atas = mints.map((m) => m.ata);
+      multi_vaults = await Promise.all(mints.map(async(m) => {
+        return (await getOrCreateAssociatedTokenAccount(anchor.getProvider().connection, initializer, m.mint, multi_pool, true)).address
+      }));
+      multi_user_lp = await getAssociatedTokenAddress(multi_mint_lp, initializer.publicKey, false, tokenProgram);
+      multi_pool_lp = await getAssociatedTokenAddress(multi_mint_lp, multi_pool, true, tokenProgram);
+      const tx = await program.methods.multiInitialize(
+        multi_seed,
+        30,
+        initializer.publicKey,
+        [3334, 3333, 3333]
+      )
+      .accountsStrict({
+        initializer: initializer.publicKey,
+        mintLp: multi_mint_lp,
+        pool: multi_pool,
+        poolLp: multi_pool_lp,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .remainingAccounts(multiAccounts(multi_vaults))
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Donate to a multi-asset pool vault before the first deposit", async () => {
+    try {
+      const tx = await transfer(anchor.getProvider().connection, initializer, multi_user_atas[0], multi_vaults[0], initializer, 1);
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Multi deposit", async () => {
+    try {
+      const tx = await program.methods.multiDeposit(
+        new BN(998_000),
+        [new BN(1_000_000), new BN(1_000_000), new BN(1_000_000)],
+        new BN(Math.floor(new Date().getTime()/1000) + 600)
+      )
+      .accountsStrict({
+        user: initializer.publicKey,
+        mintLp: multi_mint_lp,
+        userLp: multi_user_lp,
+        poolLp: multi_pool_lp,
+        pool: multi_pool,
+        blocked: blockedFor(multi_pool, initializer.publicKey),
+        blockedLp: blockedFor(multi_pool, initializer.publicKey),
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .remainingAccounts(multiAccounts([...multi_vaults, ...multi_user_atas, ...multi_user_atas.map(() => blockedFor(multi_pool, initializer.publicKey))]))
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      // The minimum liquidity is locked in the pool's own LP account
+      assert.equal(Number((await getAccount(anchor.getProvider().connection, multi_pool_lp)).amount), 1_000);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Multi swap", async () => {
+    try {
+      const tx = await program.methods.multiSwap(
+        0,
+        2,
+        new BN(10_000),
+        new BN(9_800),
+        new BN(Math.floor(new Date().getTime()/1000) + 600)
+      )
+      .accountsStrict({
+        user: initializer.publicKey,
+        userIn: multi_user_atas[0],
+        userOut: multi_user_atas[2],
+        pool: multi_pool,
+        blocked: blockedFor(multi_pool, initializer.publicKey),
+        blockedIn: blockedFor(multi_pool, initializer.publicKey),
+        blockedOut: blockedFor(multi_pool, initializer.publicKey),
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID
+      })
+      .remainingAccounts(multiAccounts(multi_vaults))
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Fail to multi swap to a blocked recipient", async () => {
+    const blocked = blockedFor(multi_pool, user.publicKey);
+    try {
+      await confirmTx(await program.methods.multiBlockWallet(user.publicKey).accountsStrict({
+        user: initializer.publicKey,
+        blocked,
+        adminQueue: null,
+        pool: multi_pool,
+        systemProgram: SystemProgram.programId
+      }).signers([initializer]).rpc());
+      const mint_out = (await getAccount(anchor.getProvider().connection, multi_user_atas[2])).mint;
+      const recipient = await createAccount(anchor.getProvider().connection, initializer, mint_out, user.publicKey, Keypair.generate());
+      const tx = await program.methods.multiSwap(
+        0,
+        2,
+        new BN(10_000),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600)
+      )
+      .accountsStrict({
+        user: initializer.publicKey,
+        userIn: multi_user_atas[0],
+        userOut: recipient,
+        pool: multi_pool,
+        blocked: blockedFor(multi_pool, initializer.publicKey),
+        blockedIn: blockedFor(multi_pool, initializer.publicKey),
+        blockedOut: blocked,
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID
+      })
+      .remainingAccounts(multiAccounts(multi_vaults))
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Swap should fail to a blocked recipient", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "WalletBlocked") {
+        throw (e)
+      }
+    }
+    await confirmTx(await program.methods.multiUnblockWallet().accountsStrict({
+      user: initializer.publicKey,
+      blocked,
+      adminQueue: null,
+      pool: multi_pool
+    }).signers([initializer]).rpc());
+  });
+
+  it("Lock multi-asset pool", async () => {
+    try {
+      const tx = await program.methods.multiLock()
+      .accountsStrict({
+        user: initializer.publicKey,
+        pool: multi_pool
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Fail to multi swap while locked", async () => {
+    try {
+      const tx = await program.methods.multiSwap(
+        0,
+        2,
+        new BN(10_000),
+        new BN(1),
+        new BN(Math.floor(new Date().getTime()/1000) + 600)
+      )
+      .accountsStrict({
+        user: initializer.publicKey,
+        userIn: multi_user_atas[0],
+        userOut: multi_user_atas[2],
+        pool: multi_pool,
+        blocked: blockedFor(multi_pool, initializer.publicKey),
+        blockedIn: blockedFor(multi_pool, initializer.publicKey),
+        blockedOut: blockedFor(multi_pool, initializer.publicKey),
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID
+      })
+      .remainingAccounts(multiAccounts(multi_vaults))
+      .signers([
+        initializer
+      ]).rpc();
+      assert.fail("Swap should fail while the pool is locked", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "PoolLocked") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Unlock multi-asset pool", async () => {
+    try {
+      const tx = await program.methods.multiUnlock()
+      .accountsStrict({
+        user: initializer.publicKey,
+        pool: multi_pool
+      })
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Multi-asset pool guardian can pause but not unpause", async () => {
+    const accounts = (signer: Keypair) => ({
+      user: signer.publicKey,
+      pool: multi_pool
+    });
+    await confirmTx(await program.methods.multiSetRoles(user.publicKey, null, null).accountsStrict(accounts(initializer)).signers([initializer]).rpc());
+    await confirmTx(await program.methods.multiLock().accountsStrict(accounts(user)).signers([user]).rpc());
+    try {
+      const tx = await program.methods.multiUnlock().accountsStrict(accounts(user)).signers([user]).rpc();
+      assert.fail("Guardian should not be able to unlock", tx);
+    } catch(e) {
+      let err = e as anchor.AnchorError;
+      if(err.error?.errorCode.code !== "InvalidAuthority") {
+        throw (e)
+      }
+    }
+    await confirmTx(await program.methods.multiUnlock().accountsStrict(accounts(initializer)).signers([initializer]).rpc());
+    await confirmTx(await program.methods.multiSetRoles(null, null, null).accountsStrict(accounts(initializer)).signers([initializer]).rpc());
+  });
+
+  it("Multi single-sided deposit", async () => {
+    try {
+      const tx = await program.methods.multiDepositSingle(
+        1,
+        new BN(30_000),
+        new BN(9_000),
+        new BN(Math.floor(new Date().getTime()/1000) + 600)
+      )
+      .accountsStrict({
+        user: initializer.publicKey,
+        mintLp: multi_mint_lp,
+        userLp: multi_user_lp,
+        poolLp: multi_pool_lp,
+        pool: multi_pool,
+        blocked: blockedFor(multi_pool, initializer.publicKey),
+        blockedLp: blockedFor(multi_pool, initializer.publicKey),
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .remainingAccounts(multiAccounts([...multi_vaults, multi_user_atas[1], blockedFor(multi_pool, initializer.publicKey)]))
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Multi single-sided withdraw", async () => {
+    try {
+      const tx = await program.methods.multiWithdrawSingle(
+        2,
+        new BN(10_000),
+        new BN(29_000),
+        new BN(Math.floor(new Date().getTime()/1000) + 600)
+      )
+      .accountsStrict({
+        user: initializer.publicKey,
+        mintLp: multi_mint_lp,
+        userLp: multi_user_lp,
+        pool: multi_pool,
+        blocked: blockedFor(multi_pool, initializer.publicKey),
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID
+      })
+      .remainingAccounts(multiAccounts([...multi_vaults, multi_user_atas[2], blockedFor(multi_pool, initializer.publicKey)]))
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Multi withdraw", async () => {
+    try {
+      const tx = await program.methods.multiWithdraw(
+        new BN(500_000),
+        [new BN(1), new BN(1), new BN(1)],
+        new BN(Math.floor(new Date().getTime()/1000) + 600)
+      )
+      .accountsStrict({
+        user: initializer.publicKey,
+        mintLp: multi_mint_lp,
+        userLp: multi_user_lp,
+        pool: multi_pool,
+        blocked: blockedFor(multi_pool, initializer.publicKey),
+        member: null,
+        gateToken: null,
+        tokenProgram: TOKEN_PROGRAM_ID
+      })
+      .remainingAccounts(multiAccounts([...multi_vaults, ...multi_user_atas]))
+      .signers([
+        initializer
+      ]).rpc();
+      await confirmTx(tx);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
+
+  it("Queue multi-asset pool admin actions behind a timelock", async () => {
+    const multi_queue = PublicKey.findProgramAddressSync([Buffer.from("admin_queue"), multi_pool.toBuffer()], program.programId)[0];
+    const multi_member = PublicKey.findProgramAddressSync([Buffer.from("member"), multi_pool.toBuffer(), user.publicKey.toBuffer()], program.programId)[0];
+    const updateAccounts = { user: initializer.publicKey, pool: multi_pool };
+    const queueAccounts = { ...updateAccounts, adminQueue: multi_queue, systemProgram: SystemProgram.programId };
+    const actionAccounts = { ...updateAccounts, adminQueue: multi_queue };
+    const memberAccounts = (adminQueue: PublicKey | null) => ({
+      user: initializer.publicKey,
+      member: multi_member,
+      adminQueue,
+      pool: multi_pool,
+      systemProgram: SystemProgram.programId
+    });
+    try {
+      await confirmTx(await program.methods.multiSetTimelockDelay(new BN(24 * 60 * 60)).accountsStrict(updateAccounts).signers([initializer]).rpc());
+      try {
+        const tx = await program.methods.multiSetFee(5000).accountsStrict(updateAccounts).signers([initializer]).rpc();
+        assert.fail("Fee change should need the timelock", tx);
+      } catch(e) {
+        let err = e as anchor.AnchorError;
+        if(err.error?.errorCode.code !== "TimelockActive") {
+          throw (e)
+        }
+      }
+      try {
+        const tx = await program.methods.multiAddMember(user.publicKey).accountsStrict(memberAccounts(null)).signers([initializer]).rpc();
+        assert.fail("Adding a member should need the timelock", tx);
+      } catch(e) {
+        let err = e as anchor.AnchorError;
+        if(err.error?.errorCode.code !== "TimelockActive") {
+          throw (e)
+        }
+      }
+      await confirmTx(await program.methods.multiQueueAction(encodeAction({ setFee: { fee: 5000 } })).accountsStrict(queueAccounts).signers([initializer]).rpc());
+      await confirmTx(await program.methods.multiQueueAction(encodeAction({ addMember: { wallet: user.publicKey } })).accountsStrict(queueAccounts).signers([initializer]).rpc());
+      await confirmTx(await program.methods.multiCancelAction(new BN(0)).accountsStrict(actionAccounts).signers([initializer]).rpc());
+      try {
+        const tx = await program.methods.multiAddMember(user.publicKey).accountsStrict(memberAccounts(multi_queue)).signers([initializer]).rpc();
+        assert.fail("Queued member should not be added before its ETA", tx);
+      } catch(e) {
+        let err = e as anchor.AnchorError;
+        if(err.error?.errorCode.code !== "ActionNotReady") {
+          throw (e)
+        }
+      }
+      // Pausing stays immediate
+      const tx = await program.methods.multiLock().accountsStrict(updateAccounts).signers([initializer]).rpc();
+      await confirmTx(tx);
+      assert.isTrue((await program.account.multiPool.fetch(multi_pool)).locked);
+      assert.deepEqual((await program.account.adminQueue.fetch(multi_queue)).actions.map((a) => a.id.toNumber()), [1]);
+      console.log("Your transaction signature", tx);
+    } catch(e) {
+      console.error(e);
+      throw(e)
+    }
+  });
 });
 
 // Helpers